@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use warp::reply::{json, Json};
+
+use crate::{actions::Result, http::query::queues::CreateQueueRequest, node::Manager};
+
+/// Create queue at runtime.
+///
+/// Requires queue name, that may only contain alphanumeric characters, dashes and underscores.
+/// Created queue uses default queue configuration, and is kept across restarts.
+///
+/// Returns empty response.
+pub async fn create_queue(manager: Arc<Manager<'_>>, request: CreateQueueRequest) -> Result<Json> {
+    manager.create_queue(&request.name).await?;
+
+    Ok(json(&()))
+}
+
+#[cfg(test)]
+mod tests {
+    use once_cell::sync::Lazy;
+
+    use crate::{
+        config::{
+            replication::{Replication, ReplicationConfig},
+            Config,
+        },
+        http::query::{push::PushRequest, queues::CreateQueueRequest, size::SizeResponse},
+        init_application, test_json_request, test_request,
+        utils::testing::MEMORY_CONFIG as CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_create_queue() {
+        let app = init_application!(&CONFIG);
+
+        let response = test_request!(
+            app,
+            "POST",
+            "/queues",
+            &CreateQueueRequest {
+                name: "created".into()
+            }
+        )
+        .await;
+        assert_eq!(response.status(), 200);
+
+        test_request!(
+            app,
+            "POST",
+            "/created",
+            &PushRequest {
+                body: "Hello, world".into(),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/created/size");
+        assert_eq!(size.size, 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_invalid_queue() {
+        let app = init_application!(&CONFIG);
+
+        for (name, status) in [
+            ("test", 409),
+            ("", 400),
+            ("../test", 400),
+            ("metrics", 400),
+            ("queues", 400),
+        ]
+        .iter()
+        {
+            let response = test_request!(
+                app,
+                "POST",
+                "/queues",
+                &CreateQueueRequest {
+                    name: (*name).into()
+                }
+            )
+            .await;
+
+            assert_eq!(response.status(), *status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_queue_with_replication() {
+        static CONFIG: Lazy<Config> = Lazy::new(|| Config {
+            persistence: None,
+            replication: Some(ReplicationConfig {
+                mode: Replication::Primary,
+                primary: None,
+                replica: None,
+            }),
+            ..Default::default()
+        });
+
+        let app = init_application!(&CONFIG);
+
+        let response = test_request!(
+            app,
+            "POST",
+            "/queues",
+            &CreateQueueRequest {
+                name: "created".into()
+            }
+        )
+        .await;
+        assert_eq!(response.status(), 409);
+
+        let response = test_request!(app, "GET", "/created/size").await;
+        assert_eq!(response.status(), 404);
+    }
+}
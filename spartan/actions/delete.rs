@@ -1,12 +1,13 @@
 use std::sync::Arc;
 
-use spartan_lib::core::dispatcher::PositionBasedDelete;
+use chrono::Utc;
+use spartan_lib::core::{dispatcher::PositionBasedDelete, payload::Status};
 use warp::reply::{json, Json};
 
 use crate::{
     actions::{QueueError, Result},
     http::query::delete::{DeleteRequest, DeleteResponse},
-    node::{event::Event, Manager},
+    node::{counters::Counter, event::Event, Manager},
 };
 
 /// Delete message from queue.
@@ -23,19 +24,26 @@ pub async fn delete(
         .log_event(&name, &manager, Event::Delete(request.id))
         .await?;
 
-    let message = queue
+    let mut message = queue
         .database()
         .await
         .delete(request.id)
         .ok_or(QueueError::MessageNotFound)?;
 
+    queue.counters().add(Counter::Delete, 1);
+
+    message.acknowledge(Utc::now().timestamp());
+
     Ok(json(&DeleteResponse::from(message)))
 }
 
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
-    use spartan_lib::{core::payload::Identifiable, uuid::Uuid};
+    use spartan_lib::{
+        core::{message::Outcome, payload::Identifiable},
+        uuid::Uuid,
+    };
 
     use crate::{
         http::query::{
@@ -86,6 +94,10 @@ mod tests {
             test_json_request!(app, "DELETE", "/test", &DeleteRequest { id: pop.id });
 
         assert_eq!(delete.message.id(), pop.id);
+        assert_eq!(
+            delete.message.history().attempts()[0].outcome(),
+            Some(Outcome::Deleted)
+        );
 
         let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
 
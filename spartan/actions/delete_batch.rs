@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use spartan_lib::core::dispatcher::PositionBasedDelete;
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::Result,
+    http::query::batch::{BatchRequest, BatchResponse},
+    node::{counters::Counter, event::Event, Manager},
+};
+
+/// Delete multiple messages from queue.
+///
+/// Requires IDs of messages being deleted, returns IDs of deleted messages.
+///
+/// Messages, that were not found, are skipped. Whole batch is logged as single event.
+pub async fn delete_batch(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    request: BatchRequest,
+) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+
+    queue
+        .log_event(&name, &manager, Event::DeleteBatch(request.ids.clone()))
+        .await?;
+
+    let mut database = queue.database().await;
+
+    let ids: Vec<_> = request
+        .ids
+        .into_iter()
+        .filter(|id| database.delete(*id).is_some())
+        .collect();
+
+    queue.counters().add(Counter::Delete, ids.len());
+
+    Ok(json(&BatchResponse::from(ids)))
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::{
+        http::query::{
+            batch::{test_response::TestPopBatchResponse, BatchRequest, BatchResponse},
+            push::PushRequest,
+            size::SizeResponse,
+        },
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_delete_batch() {
+        let app = init_application!(&CONFIG);
+
+        for body in &["First", "Second", "Third"] {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        let pop: TestPopBatchResponse = test_json_request!(app, "GET", "/test/batch?count=2");
+        let mut ids: Vec<_> = pop.messages.iter().map(|message| message.id).collect();
+        ids.push(Uuid::new_v4());
+
+        let delete: BatchResponse = test_json_request!(
+            app,
+            "DELETE",
+            "/test/batch",
+            &BatchRequest { ids: ids.clone() }
+        );
+        assert_eq!(delete.ids, &ids[..2]);
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 1);
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use warp::reply::{json, Json};
+
+use crate::{actions::Result, node::Manager};
+
+/// Delete queue, that was created at runtime.
+///
+/// Doesn't require any input, returns empty response.
+///
+/// All messages and persisted files of queue are removed. Queues, that are declared in config, can't be deleted.
+pub async fn delete_queue(manager: Arc<Manager<'_>>, name: String) -> Result<Json> {
+    manager.delete_queue(&name).await?;
+
+    Ok(json(&()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::query::queues::CreateQueueRequest, init_application, test_request,
+        utils::testing::MEMORY_CONFIG as CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_delete_queue() {
+        let app = init_application!(&CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/queues",
+            &CreateQueueRequest {
+                name: "created".into()
+            }
+        )
+        .await;
+
+        let response = test_request!(app, "DELETE", "/queues/created").await;
+        assert_eq!(response.status(), 200);
+
+        let response = test_request!(app, "GET", "/created/size").await;
+        assert_eq!(response.status(), 404);
+
+        let response = test_request!(app, "DELETE", "/queues/created").await;
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_delete_config_queue() {
+        let app = init_application!(&CONFIG);
+
+        let response = test_request!(app, "DELETE", "/queues/test").await;
+        assert_eq!(response.status(), 409);
+
+        let response = test_request!(app, "GET", "/test/size").await;
+        assert_eq!(response.status(), 200);
+    }
+}
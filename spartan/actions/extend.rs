@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use spartan_lib::core::{db::Database, dispatcher::StatusAwareDispatcher, payload::Status};
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::{QueueError, Result},
+    http::query::extend::ExtendRequest,
+    node::{event::Event, Manager},
+};
+
+/// Extends timeout of message, that is currently being processed.
+///
+/// Requires ID of reserved message, returns empty response.
+///
+/// Message timeout is restarted, and max timeout is replaced with provided one, if there is any.
+/// Resulting deadline is logged, so replay doesn't restart timeout once again.
+pub async fn extend(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    request: ExtendRequest,
+) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+
+    let deadline = {
+        let database = queue.database().await;
+
+        let message = database
+            .get(request.id)
+            .filter(|message| message.requeueable())
+            .ok_or(QueueError::MessageNotFound)?;
+
+        let timeout = request.timeout.unwrap_or(*message.time().timeout().max());
+
+        Utc::now().timestamp() + i64::from(timeout)
+    };
+
+    queue
+        .log_event(
+            &name,
+            &manager,
+            Event::Extend(request.id, request.timeout, deadline),
+        )
+        .await?;
+
+    queue
+        .database()
+        .await
+        .extend(request.id, request.timeout, deadline)
+        .ok_or(QueueError::MessageNotFound)?;
+
+    Ok(json(&()))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use uuid::Uuid;
+
+    use crate::{
+        http::query::{
+            extend::ExtendRequest, pop::test_response::TestPopResponse, push::PushRequest,
+            size::SizeResponse,
+        },
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_empty_extend() {
+        let app = init_application!(&CONFIG);
+        let resp = test_request!(
+            app,
+            "POST",
+            "/test/extend",
+            &ExtendRequest {
+                id: Uuid::new_v4(),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        assert_eq!(*resp.body(), Bytes::from_static(b"Message not found"));
+    }
+
+    #[tokio::test]
+    async fn test_message_extend() {
+        let app = init_application!(&CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                max_tries: Some(2),
+                timeout: Some(0),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+
+        let extend = test_request!(
+            app,
+            "POST",
+            "/test/extend",
+            &ExtendRequest {
+                id: pop.id,
+                timeout: Some(600),
+            }
+        )
+        .await;
+
+        assert_eq!(*extend.body(), Bytes::from_static(b"null"));
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+
+        assert_eq!(size.size, 1);
+    }
+}
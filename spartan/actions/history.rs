@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use spartan_lib::{core::db::Database, uuid::Uuid};
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::{QueueError, Result},
+    http::query::history::HistoryResponse,
+    node::Manager,
+};
+
+/// Get message reservation history.
+///
+/// Requires ID of message in path, returns its latest reservations with their outcomes.
+///
+/// Message status is not changed.
+pub async fn history(manager: Arc<Manager<'_>>, name: String, id: Uuid) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+    let database = queue.database().await;
+    let message = database.get(id).ok_or(QueueError::MessageNotFound)?;
+
+    Ok(json(&HistoryResponse::from(message.history())))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use spartan_lib::core::message::Outcome;
+    use uuid::Uuid;
+
+    use crate::{
+        http::query::{
+            history::test_response::TestHistoryResponse, pop::test_response::TestPopResponse,
+            push::PushRequest, requeue::RequeueRequest,
+        },
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_empty_history() {
+        let app = init_application!(&CONFIG);
+        let resp = test_request!(
+            app,
+            "GET",
+            &format!("/test/messages/{}/history", Uuid::new_v4())
+        )
+        .await;
+
+        assert_eq!(*resp.body(), Bytes::from_static(b"Message not found"));
+    }
+
+    #[tokio::test]
+    async fn test_history() {
+        let app = init_application!(&CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                max_tries: Some(2),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        let uri = format!("/test/messages/{}/history", pop.id);
+
+        let history: TestHistoryResponse = test_json_request!(app, "GET", &uri);
+        assert_eq!(history.history.len(), 1);
+        assert_eq!(history.history[0].outcome, None);
+        assert!(history.history[0].finished_at.is_none());
+
+        test_request!(app, "POST", "/test/requeue", &RequeueRequest { id: pop.id }).await;
+
+        test_request!(app, "GET", "/test").await;
+
+        let history: TestHistoryResponse = test_json_request!(app, "GET", &uri);
+        assert_eq!(history.history.len(), 2);
+        assert_eq!(history.history[0].outcome, Some(Outcome::Requeued));
+        assert!(history.history[0].finished_at.unwrap() >= history.history[0].obtained_at);
+        assert_eq!(history.history[1].outcome, None);
+    }
+}
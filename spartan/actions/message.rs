@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use spartan_lib::{core::db::Database, uuid::Uuid};
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::{QueueError, Result},
+    http::query::pop::PopResponse,
+    node::Manager,
+};
+
+/// Get message by ID.
+///
+/// Requires ID of message in path, returns message in the same format as pop.
+///
+/// Message status is not changed.
+pub async fn message(manager: Arc<Manager<'_>>, name: String, id: Uuid) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+    let database = queue.database().await;
+    let message = database.get(id).ok_or(QueueError::MessageNotFound)?;
+
+    Ok(json(&PopResponse::from(message)))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use uuid::Uuid;
+
+    use crate::{
+        http::query::{
+            pop::test_response::TestPopResponse,
+            push::{PushRequest, PushResponse},
+        },
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_empty_message() {
+        let app = init_application!(&CONFIG);
+        let resp = test_request!(app, "GET", &format!("/test/messages/{}", Uuid::new_v4())).await;
+
+        assert_eq!(*resp.body(), Bytes::from_static(b"Message not found"));
+    }
+
+    #[tokio::test]
+    async fn test_message() {
+        let app = init_application!(&CONFIG);
+
+        let push: PushResponse = test_json_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                ..Default::default()
+            }
+        );
+
+        let uri = format!("/test/messages/{}", push.id);
+
+        let message: TestPopResponse = test_json_request!(app, "GET", &uri);
+        assert_eq!(message.id, push.id);
+        assert_eq!(&*message.body, "Hello, world");
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(pop.id, push.id);
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use spartan_lib::core::db::Database;
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::Result,
+    http::query::messages::{MessagesQuery, MessagesResponse},
+    node::Manager,
+};
+
+/// Browse queue messages.
+///
+/// Accepts optional `status` filter, page `limit` and `cursor` in query parameters,
+/// returns messages in insertion order with cursor of the next page.
+///
+/// Page limit is kept between 1 and 1000 messages.
+///
+/// Message status is not changed. Next page cursor is omitted, if there are no messages left.
+pub async fn messages(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    query: MessagesQuery,
+) -> Result<Json> {
+    let limit = query.limit();
+    let queue = manager.queue(&name)?;
+    let database = queue.database().await;
+
+    let mut page = database
+        .iter_after(query.cursor)
+        .filter(|(_, message)| match query.status {
+            Some(status) => status.matches(message),
+            None => true,
+        })
+        .take(limit + 1)
+        .collect::<Vec<_>>();
+
+    let next_cursor = if page.len() > limit {
+        page.truncate(limit);
+        page.last().map(|(cursor, _)| *cursor)
+    } else {
+        None
+    };
+
+    let messages = page.into_iter().map(|(_, message)| message).collect();
+
+    Ok(json(&MessagesResponse::new(messages, next_cursor)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::query::{messages::test_response::TestMessagesResponse, push::PushRequest},
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_messages() {
+        let app = init_application!(&CONFIG);
+
+        let page: TestMessagesResponse = test_json_request!(app, "GET", "/test/messages");
+        assert!(page.messages.is_empty());
+        assert!(page.next_cursor.is_none());
+
+        for (body, delay) in &[("First", None), ("Second", None), ("Third", Some(600))] {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    delay: *delay,
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        test_request!(app, "GET", "/test").await;
+
+        let page: TestMessagesResponse = test_json_request!(app, "GET", "/test/messages?limit=2");
+        let bodies: Vec<_> = page.messages.iter().map(|message| &*message.body).collect();
+        assert_eq!(bodies, ["First", "Second"]);
+
+        let uri = format!(
+            "/test/messages?limit=2&cursor={}",
+            page.next_cursor.unwrap()
+        );
+        let page: TestMessagesResponse = test_json_request!(app, "GET", &uri);
+        let bodies: Vec<_> = page.messages.iter().map(|message| &*message.body).collect();
+        assert_eq!(bodies, ["Third"]);
+        assert!(page.next_cursor.is_none());
+
+        // Empty page without cursor would look like the last one
+        let page: TestMessagesResponse = test_json_request!(app, "GET", "/test/messages?limit=0");
+        assert_eq!(page.messages.len(), 1);
+        assert!(page.next_cursor.is_some());
+
+        for (status, body) in &[
+            ("transit", "First"),
+            ("available", "Second"),
+            ("delayed", "Third"),
+        ] {
+            let uri = format!("/test/messages?status={}", status);
+            let page: TestMessagesResponse = test_json_request!(app, "GET", &uri);
+            let bodies: Vec<_> = page.messages.iter().map(|message| &*message.body).collect();
+            assert_eq!(bodies, [*body]);
+        }
+
+        let page: TestMessagesResponse =
+            test_json_request!(app, "GET", "/test/messages?status=exhausted");
+        assert!(page.messages.is_empty());
+    }
+}
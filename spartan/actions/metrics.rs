@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use spartan_lib::core::db::{Database, StatusAwareDatabase};
+use warp::{http::response::Builder, hyper::Body, reply::Response};
+
+use crate::{
+    actions::Result,
+    node::{metrics::QueueSample, Manager},
+};
+
+/// Export server metrics.
+///
+/// Doesn't require any input, returns metrics of all queues in Prometheus text format.
+///
+/// Queue depth and in-flight messages are counted at the moment of request.
+pub async fn metrics(manager: Arc<Manager<'_>>) -> Result<Response> {
+    let queues = manager.node().iter().collect::<Vec<_>>();
+    let mut samples = Vec::with_capacity(queues.len());
+    let now = Utc::now().timestamp();
+
+    for (name, queue) in queues.iter() {
+        let (size, in_flight) = {
+            let database = queue.database().await;
+            (database.len(), database.status_counts(now).transit)
+        };
+
+        samples.push(QueueSample {
+            name,
+            size,
+            in_flight,
+            counters: queue.counters(),
+        });
+    }
+
+    Ok(Builder::default()
+        .header("content-type", "text/plain; version=0.0.4")
+        .body(Body::from(manager.metrics().render(&samples)))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use crate::{
+        http::query::push::PushRequest, init_application, test_request, utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let app = init_application!(&CONFIG);
+
+        for _ in 0..2 {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from("Hello, world").into_boxed_str(),
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        test_request!(app, "GET", "/test").await;
+        test_request!(app, "GET", "/test/size").await;
+
+        let resp = test_request!(app, "GET", "/metrics").await;
+        let body = from_utf8(resp.body()).unwrap();
+
+        assert!(body.contains("spartan_queue_messages{queue=\"test\"} 2\n"));
+        assert!(body.contains("spartan_queue_in_flight_messages{queue=\"test\"} 1\n"));
+        assert!(
+            body.contains("spartan_queue_operations_total{queue=\"test\",operation=\"pop\"} 1\n")
+        );
+        assert!(body.contains("spartan_action_requests_total{action=\"push\",status=\"200\"} 2\n"));
+        assert!(body.contains("spartan_action_duration_seconds_count{action=\"size\"} 1\n"));
+    }
+}
@@ -3,7 +3,7 @@ use std::{fmt::Display, result::Result as StdResult};
 use spartan_lib::core::message::builder::BuilderError;
 use thiserror::Error as ThisError;
 use warp::{
-    http::response::Builder,
+    http::{response::Builder, Error as HttpError},
     hyper::{Body, StatusCode},
     reply::Response,
     Reply,
@@ -12,21 +12,84 @@ use warp::{
 /// Clear queue
 pub mod clear;
 
+/// Create queue at runtime
+pub mod create_queue;
+
 /// Delete message from queue
 pub mod delete;
 
+/// Delete multiple messages from queue
+pub mod delete_batch;
+
+/// Delete queue, that was created at runtime
+pub mod delete_queue;
+
+/// Extend message timeout
+pub mod extend;
+
+/// Get message reservation history
+pub mod history;
+
+/// Get message by ID
+pub mod message;
+
+/// Export server metrics
+pub mod metrics;
+
+/// Browse queue messages
+pub mod messages;
+
+/// Negatively acknowledge message
+pub mod nack;
+
 /// Pop message from queue
 pub mod pop;
 
+/// Pop multiple messages from queue
+pub mod pop_batch;
+
+/// Pop raw message body from queue
+pub mod pop_raw;
+
+/// Remove messages, that match filter
+pub mod purge;
+
 /// Push message to queue
 pub mod push;
 
+/// Push batch of messages to queue
+pub mod push_batch;
+
+/// Push raw message body to queue
+pub mod push_raw;
+
+/// List queues of node
+pub mod queues;
+
 /// Requeue message back
 pub mod requeue;
 
+/// Requeue multiple messages back
+pub mod requeue_batch;
+
+/// Create recurring message schedule
+pub mod schedule;
+
+/// List recurring message schedules
+pub mod schedules;
+
 /// Get queue size
 pub mod size;
 
+/// Get queue statistics
+pub mod stats;
+
+/// Delete recurring message schedule
+pub mod unschedule;
+
+/// Update message, that is not reserved
+pub mod update;
+
 pub type Result<T> = StdResult<T, ResponseError>;
 
 pub struct ResponseError {
@@ -69,10 +132,30 @@ pub enum QueueError {
     MessageNotFound,
     #[error("Unable to compose message")]
     MessageCompose(#[from] BuilderError),
+    #[error("Batch is too large")]
+    BatchTooLarge,
+    #[error("Message is reserved")]
+    MessageReserved,
+    #[error("Max tries must be greater than zero")]
+    InvalidMaxTries,
+    #[error("Purge filter is empty")]
+    EmptyFilter,
+    #[error("Failure reason is too long")]
+    ReasonTooLong,
 }
 
+/// Invalid response, that was built by action
+impl RespondableError for HttpError {}
+
 impl RespondableError for QueueError {
     fn status_code(&self) -> StatusCode {
-        StatusCode::NOT_FOUND
+        match self {
+            QueueError::BatchTooLarge
+            | QueueError::InvalidMaxTries
+            | QueueError::EmptyFilter
+            | QueueError::ReasonTooLong => StatusCode::BAD_REQUEST,
+            QueueError::MessageReserved => StatusCode::CONFLICT,
+            _ => StatusCode::NOT_FOUND,
+        }
     }
 }
@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::{requeue::requeue_message, QueueError, Result},
+    config::queue::DEFAULT_MAX_NACK_DELAY,
+    http::query::nack::NackRequest,
+    node::Manager,
+};
+
+/// Max length of failure reason in bytes
+const MAX_REASON_LENGTH: usize = 1024;
+
+/// Negatively acknowledge message, returning it back to queue.
+///
+/// Requires ID of message being returned, failure reason and delay in seconds are optional. Returns empty response.
+///
+/// Failure reason is kept in message state, along with reasons of previous failures.
+/// Reason longer than 1024 bytes is rejected.
+///
+/// Delay is clamped to queue max nack delay.
+/// If delay is not provided and queue has retry policy, then message is delayed according to it.
+///
+/// Message with exhausted tries is moved to dead letter queue, if there is any.
+pub async fn nack(manager: Arc<Manager<'_>>, name: String, request: NackRequest) -> Result<Json> {
+    if matches!(&request.reason, Some(reason) if reason.len() > MAX_REASON_LENGTH) {
+        return Err(QueueError::ReasonTooLong.into());
+    }
+
+    let max_delay = manager
+        .queue_config(&name)
+        .and_then(|config| config.max_nack_delay)
+        .unwrap_or(DEFAULT_MAX_NACK_DELAY);
+
+    let delay = request.delay.map(|delay| delay.min(max_delay));
+
+    requeue_message(&manager, &name, request.id, request.reason, delay).await?;
+
+    Ok(json(&()))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use once_cell::sync::Lazy;
+    use serde_json::Value;
+    use uuid::Uuid;
+    use warp::hyper::StatusCode;
+
+    use super::MAX_REASON_LENGTH;
+    use crate::{
+        config::{queue::QueueConfig, Config},
+        http::query::{nack::NackRequest, pop::test_response::TestPopResponse, push::PushRequest},
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    static MAX_DELAY_CONFIG: Lazy<Config> = Lazy::new(|| Config {
+        queue_config: vec![(
+            String::from("test").into_boxed_str(),
+            QueueConfig {
+                max_nack_delay: Some(0),
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    });
+
+    #[tokio::test]
+    async fn test_empty_nack() {
+        let app = init_application!(&CONFIG);
+        let resp = test_request!(
+            app,
+            "POST",
+            "/test/nack",
+            &NackRequest {
+                id: Uuid::new_v4(),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        assert_eq!(*resp.body(), Bytes::from_static(b"Message not found"));
+    }
+
+    #[tokio::test]
+    async fn test_nack() {
+        let app = init_application!(&CONFIG);
+
+        for body in &["First", "Second"] {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    max_tries: Some(3),
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(&*pop.body, "First");
+
+        test_request!(
+            app,
+            "POST",
+            "/test/nack",
+            &NackRequest {
+                id: pop.id,
+                reason: Some("Connection refused".into()),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: Value = test_json_request!(app, "GET", "/test");
+        assert_eq!(pop["body"], "First");
+        assert_eq!(pop["state"]["failures"][0], "Connection refused");
+
+        let id: Uuid = serde_json::from_value(pop["id"].clone()).unwrap();
+
+        test_request!(
+            app,
+            "POST",
+            "/test/nack",
+            &NackRequest {
+                id,
+                reason: Some("Timeout".into()),
+                delay: Some(600),
+            }
+        )
+        .await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(&*pop.body, "Second");
+
+        let empty = test_request!(app, "GET", "/test").await;
+        assert_eq!(*empty.body(), Bytes::from_static(b"No message available"));
+    }
+
+    #[tokio::test]
+    async fn test_nack_long_reason() {
+        let app = init_application!(&CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                max_tries: Some(3),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+
+        let resp = test_request!(
+            app,
+            "POST",
+            "/test/nack",
+            &NackRequest {
+                id: pop.id,
+                reason: Some("a".repeat(MAX_REASON_LENGTH + 1).into_boxed_str()),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            *resp.body(),
+            Bytes::from_static(b"Failure reason is too long")
+        );
+
+        test_request!(
+            app,
+            "POST",
+            "/test/nack",
+            &NackRequest {
+                id: pop.id,
+                reason: Some("a".repeat(MAX_REASON_LENGTH).into_boxed_str()),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: Value = test_json_request!(app, "GET", "/test");
+        assert_eq!(
+            pop["state"]["failures"][0].as_str().unwrap().len(),
+            MAX_REASON_LENGTH
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nack_max_delay() {
+        let app = init_application!(&MAX_DELAY_CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                max_tries: Some(3),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+
+        test_request!(
+            app,
+            "POST",
+            "/test/nack",
+            &NackRequest {
+                id: pop.id,
+                delay: Some(u32::MAX),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(&*pop.body, "Hello, world");
+    }
+}
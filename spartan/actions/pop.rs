@@ -1,12 +1,13 @@
 use std::sync::Arc;
 
-use spartan_lib::core::dispatcher::StatusAwareDispatcher;
+use chrono::Utc;
+use spartan_lib::core::{dispatcher::StatusAwareDispatcher, message::Message};
 use warp::reply::{json, Json};
 
 use crate::{
     actions::{QueueError, Result},
     http::query::pop::PopResponse,
-    node::{event::Event, Manager},
+    node::{counters::Counter, event::Event, Manager},
 };
 
 /// Pop message from queue.
@@ -15,28 +16,63 @@ use crate::{
 ///
 /// After reserving message, you either need to return it to queue, or delete it.
 ///
-/// Messages that are not returned after timeout are deleted by GC.
+/// Messages that are not returned after timeout are returned to queue automatically.
+///
+/// Expired messages are never returned, and are moved to dead letter queue, if there is any.
 pub async fn pop(manager: Arc<Manager<'_>>, name: String) -> Result<Json> {
-    let queue = manager.queue(&name)?;
+    pop_message(&manager, &name, |message| json(&PopResponse::from(message))).await
+}
+
+/// Reserve message from queue, and build response from it, while queue is still locked
+pub(crate) async fn pop_message<F, T>(manager: &Manager<'_>, name: &str, respond: F) -> Result<T>
+where
+    F: FnOnce(&Message) -> T,
+{
+    let queue = manager.queue(name)?;
+
+    manager.drain_expired(name, &queue).await?;
 
-    queue.log_event(&name, &manager, Event::Pop).await?;
+    let now = Utc::now().timestamp();
+
+    queue.log_event(name, manager, Event::Pop(now)).await?;
 
     let mut database = queue.database().await;
-    let message = database.pop().ok_or(QueueError::NoMessageAvailable)?;
+    let message = database.pop(now).ok_or(QueueError::NoMessageAvailable)?;
+
+    queue.counters().add(Counter::Pop, 1);
 
-    Ok(json(&PopResponse::from(message)))
+    Ok(respond(message))
 }
 
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
+    use once_cell::sync::Lazy;
 
     use crate::{
-        http::query::{pop::test_response::TestPopResponse, push::PushRequest},
+        config::{queue::QueueConfig, Config},
+        http::query::{
+            delete::DeleteRequest, pop::test_response::TestPopResponse, push::PushRequest,
+            size::SizeResponse,
+        },
         init_application, test_json_request, test_request,
         utils::testing::CONFIG,
     };
 
+    static TTL_CONFIG: Lazy<Config> = Lazy::new(|| Config {
+        queue_config: vec![(
+            String::from("test").into_boxed_str(),
+            QueueConfig {
+                dead_letter: Some(String::from("test_2").into_boxed_str()),
+                ttl: Some(0),
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    });
+
     #[tokio::test]
     async fn test_empty_pop() {
         let app = init_application!(&CONFIG);
@@ -62,4 +98,88 @@ mod tests {
         let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
         assert_eq!(&*pop.body, "Hello, world");
     }
+
+    #[tokio::test]
+    async fn test_group_pop() {
+        let app = init_application!(&CONFIG);
+
+        for (body, group_id) in &[
+            ("First", Some("order")),
+            ("Second", Some("order")),
+            ("Other", None),
+        ] {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    group_id: group_id.map(|group_id| String::from(group_id).into_boxed_str()),
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        let first: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(&*first.body, "First");
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(&*pop.body, "Other");
+
+        let pop = test_request!(app, "GET", "/test").await;
+        assert_eq!(*pop.body(), Bytes::from_static(b"No message available"));
+
+        test_request!(app, "DELETE", "/test", &DeleteRequest { id: first.id }).await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(&*pop.body, "Second");
+    }
+
+    #[tokio::test]
+    async fn test_expired_pop() {
+        let app = init_application!(&CONFIG);
+
+        for (body, ttl) in &[("Expired", Some(0)), ("Hello, world", None)] {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    ttl: *ttl,
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(&*pop.body, "Hello, world");
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_dead_letter_pop() {
+        let app = init_application!(&TTL_CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop = test_request!(app, "GET", "/test").await;
+        assert_eq!(*pop.body(), Bytes::from_static(b"No message available"));
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test_2");
+        assert_eq!(&*pop.body, "Hello, world");
+    }
 }
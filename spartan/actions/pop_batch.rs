@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use spartan_lib::core::dispatcher::StatusAwareDispatcher;
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::Result,
+    http::query::batch::{PopBatchQuery, PopBatchResponse},
+    node::{counters::Counter, event::Event, Manager},
+};
+
+/// Pop multiple messages from queue.
+///
+/// Requires max amount of messages in `count` query parameter, returns reserved messages.
+/// Count is limited to 1000 messages.
+///
+/// Messages are reserved under single queue lock, and whole batch is logged as single event.
+///
+/// Returns empty batch if there are no messages available.
+pub async fn pop_batch(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    query: PopBatchQuery,
+) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+
+    manager.drain_expired(&name, &queue).await?;
+
+    let now = Utc::now().timestamp();
+    let count = query.count();
+
+    queue
+        .log_event(&name, &manager, Event::PopBatch(count, now))
+        .await?;
+
+    let mut database = queue.database().await;
+    let messages = database.pop_many(count as usize, now);
+
+    queue.counters().add(Counter::Pop, messages.len());
+
+    Ok(json(&PopBatchResponse::from(messages)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::query::{batch::test_response::TestPopBatchResponse, push::PushRequest},
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_pop_batch() {
+        let app = init_application!(&CONFIG);
+
+        let pop: TestPopBatchResponse = test_json_request!(app, "GET", "/test/batch?count=2");
+        assert!(pop.messages.is_empty());
+
+        for body in &["First", "Second", "Third"] {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        let pop: TestPopBatchResponse = test_json_request!(app, "GET", "/test/batch?count=2");
+        let bodies: Vec<_> = pop.messages.iter().map(|message| &*message.body).collect();
+        assert_eq!(bodies, ["First", "Second"]);
+
+        let pop: TestPopBatchResponse = test_json_request!(app, "GET", "/test/batch?count=2");
+        let bodies: Vec<_> = pop.messages.iter().map(|message| &*message.body).collect();
+        assert_eq!(bodies, ["Third"]);
+
+        // Count above limit is clamped instead of rejected
+        let pop: TestPopBatchResponse =
+            test_json_request!(app, "GET", "/test/batch?count=4294967295");
+        assert!(pop.messages.is_empty());
+    }
+}
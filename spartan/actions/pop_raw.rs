@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use spartan_lib::core::payload::{Dispatchable, Identifiable};
+use warp::{
+    http::response::Builder,
+    hyper::Body,
+    reply::{Reply, Response},
+};
+
+use crate::{
+    actions::{pop::pop_message, Result},
+    node::Manager,
+};
+
+/// Pop raw message from queue.
+///
+/// Doesn't require any input, returns reserved message body as `application/octet-stream`.
+///
+/// Message ID and tries are returned in `X-Message-Id`, `X-Message-Tries` and `X-Message-Max-Tries` headers.
+pub async fn pop_raw(manager: Arc<Manager<'_>>, name: String) -> Result<Response> {
+    let response = pop_message(&manager, &name, |message| {
+        Builder::default()
+            .header("content-type", "application/octet-stream")
+            .header("x-message-id", message.id().to_string())
+            .header("x-message-tries", message.state().tries().to_string())
+            .header(
+                "x-message-max-tries",
+                message.state().max_tries().to_string(),
+            )
+            .body(Body::from(message.body().to_vec()))
+    })
+    .await?;
+
+    Ok(response?.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use crate::{
+        http::query::push::PushRequest, init_application, test_request, utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_empty_raw_pop() {
+        let app = init_application!(&CONFIG);
+
+        let pop = ::warp::test::request()
+            .method("GET")
+            .path("/test")
+            .header("accept", "application/octet-stream")
+            .reply(&app)
+            .await;
+
+        assert_eq!(*pop.body(), Bytes::from_static(b"No message available"));
+    }
+
+    #[tokio::test]
+    async fn test_raw_pop() {
+        let app = init_application!(&CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop = ::warp::test::request()
+            .method("GET")
+            .path("/test")
+            .header("accept", "application/octet-stream")
+            .reply(&app)
+            .await;
+
+        assert_eq!(*pop.body(), Bytes::from_static(b"Hello, world"));
+        assert_eq!(pop.headers()["content-type"], "application/octet-stream");
+        assert!(pop.headers().contains_key("x-message-id"));
+    }
+}
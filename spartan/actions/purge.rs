@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use spartan_lib::core::{db::Database, dispatcher::PositionBasedDelete, payload::Identifiable};
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::{QueueError, Result},
+    http::query::purge::{PurgeRequest, PurgeResponse},
+    node::{counters::Counter, event::Event, Manager},
+};
+
+/// Remove all messages, that match filter.
+///
+/// Accepts optional status, min tries, age in seconds, body substring and attributes filters,
+/// returns amount of removed messages. In dry-run mode messages are not removed,
+/// and amount of matched messages is returned instead.
+///
+/// Filter without conditions is rejected, use clear route to remove all messages.
+///
+/// IDs of matched messages are logged as single batch delete event, so replay doesn't depend on time.
+pub async fn purge(manager: Arc<Manager<'_>>, name: String, request: PurgeRequest) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+
+    if request.is_empty() {
+        return Err(QueueError::EmptyFilter.into());
+    }
+
+    let now = Utc::now();
+    let ids: Vec<_> = queue
+        .database()
+        .await
+        .iter_after(None)
+        .filter(|(_, message)| request.matches(message, now))
+        .map(|(_, message)| message.id())
+        .collect();
+
+    if request.dry_run || ids.is_empty() {
+        return Ok(json(&PurgeResponse::from(ids.len())));
+    }
+
+    queue
+        .log_event(&name, &manager, Event::DeleteBatch(ids.clone()))
+        .await?;
+
+    // Messages, that were deleted while batch was logged, are skipped
+    let mut database = queue.database().await;
+    let deleted = ids.iter().filter_map(|id| database.delete(*id)).count();
+
+    queue.counters().add(Counter::Delete, deleted);
+
+    Ok(json(&PurgeResponse::from(deleted)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use warp::hyper::StatusCode;
+
+    use crate::{
+        http::query::{
+            messages::MessageStatus,
+            purge::{PurgeRequest, PurgeResponse},
+            push::PushRequest,
+            size::SizeResponse,
+        },
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_purge() {
+        let app = init_application!(&CONFIG);
+
+        for (body, kind) in &[("First", "a"), ("Second", "b"), ("Third", "a")] {
+            let mut attributes = BTreeMap::new();
+            attributes.insert("kind".into(), (*kind).into());
+
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    attributes: Some(attributes),
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        test_request!(app, "GET", "/test").await;
+
+        let mut attributes = BTreeMap::new();
+        attributes.insert("kind".into(), "a".into());
+
+        let purge: PurgeResponse = test_json_request!(
+            app,
+            "POST",
+            "/test/purge",
+            &PurgeRequest {
+                attributes: Some(attributes.clone()),
+                dry_run: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(purge.count, 2);
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 3);
+
+        let purge: PurgeResponse = test_json_request!(
+            app,
+            "POST",
+            "/test/purge",
+            &PurgeRequest {
+                status: Some(MessageStatus::Available),
+                attributes: Some(attributes),
+                ..Default::default()
+            }
+        );
+        assert_eq!(purge.count, 1);
+
+        let purge: PurgeResponse = test_json_request!(
+            app,
+            "POST",
+            "/test/purge",
+            &PurgeRequest {
+                body_contains: Some("con".into()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(purge.count, 1);
+
+        let purge: PurgeResponse = test_json_request!(
+            app,
+            "POST",
+            "/test/purge",
+            &PurgeRequest {
+                min_tries: Some(1),
+                older_than: Some(0),
+                ..Default::default()
+            }
+        );
+        assert_eq!(purge.count, 1);
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 0);
+    }
+
+    #[tokio::test]
+    async fn test_empty_purge() {
+        let app = init_application!(&CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let resp = test_request!(
+            app,
+            "POST",
+            "/test/purge",
+            &PurgeRequest {
+                body_contains: Some("".into()),
+                ..Default::default()
+            }
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 1);
+    }
+}
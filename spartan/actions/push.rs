@@ -1,39 +1,68 @@
 use std::{convert::TryInto, sync::Arc};
 
-use maybe_owned::MaybeOwned;
-use spartan_lib::core::{dispatcher::SimpleDispatcher, message::Message};
+use spartan_lib::core::message::Message;
 use warp::reply::{json, Json};
 
 use crate::{
     actions::{QueueError, Result},
-    http::query::push::PushRequest,
-    node::{event::Event, Manager},
+    http::query::push::{PushRequest, PushResponse},
+    node::Manager,
 };
 
 /// Push message to queue.
 ///
-/// Requires message body. Offset, max tries, timeout, delay are optional.
+/// Requires message body. ID, offset, timezone, max tries, timeout, delay, delivery time, priority, TTL, attributes, deduplication ID and group ID are optional.
 ///
-/// Returns empty response.
-pub async fn push(manager: Arc<Manager<'_>>, name: String, request: PushRequest) -> Result<Json> {
+/// If TTL is not provided, then queue default TTL is used.
+///
+/// Timezone is an IANA timezone name, that replaces offset. Message times in named timezone respect DST changes.
+///
+/// Delivery time replaces delay. It must not be in the past, unless past delivery is allowed.
+/// It's either an RFC 3339 timestamp, whose offset is used as message offset if offset is not provided,
+/// or a local time without offset, that is resolved in message timezone. Ambiguous local time resolves to its first occurrence,
+/// and local time skipped by DST change is moved forward by the length of the gap.
+///
+/// Messages with the same group ID are dispatched one at a time.
+///
+/// If deduplication ID is provided, then repeated push with the same ID within queue deduplication window is discarded.
+///
+/// Push with ID, that already exists in queue, is rejected with conflict error.
+///
+/// Returns ID of pushed message, or ID of original message, if push was discarded.
+pub async fn push(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    mut request: PushRequest,
+) -> Result<Json> {
     let queue = manager.queue(&name)?;
+
+    if request.ttl.is_none() {
+        request.ttl = manager.queue_config(&name).and_then(|config| config.ttl);
+    }
+
+    let deduplication_id = request.deduplication_id.take();
     let message: Message = request.try_into().map_err(QueueError::MessageCompose)?;
 
-    queue
-        .log_event(&name, &manager, Event::Push(MaybeOwned::Borrowed(&message)))
+    let id = queue
+        .push(&name, &manager, message, deduplication_id)
         .await?;
 
-    queue.database().await.push(message);
-
-    Ok(json(&()))
+    Ok(json(&PushResponse { id }))
 }
 
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
+    use chrono::{Duration, FixedOffset, Utc};
+    use serde_json::Value;
+    use spartan_lib::uuid::Uuid;
 
     use crate::{
-        http::query::{pop::test_response::TestPopResponse, push::PushRequest},
+        http::query::{
+            pop::test_response::TestPopResponse,
+            push::{DeliveryTime, PushRequest, PushResponse},
+            size::SizeResponse,
+        },
         init_application, test_json_request, test_request,
         utils::testing::CONFIG,
     };
@@ -58,6 +87,80 @@ mod tests {
         assert_eq!(&*pop.body, "Hello, world");
     }
 
+    #[tokio::test]
+    async fn test_push_attributes() {
+        let app = init_application!(&CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                attributes: Some(
+                    vec![("content-type".into(), "text/plain".into())]
+                        .into_iter()
+                        .collect()
+                ),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+
+        assert_eq!(pop.attributes.get("content-type"), Some("text/plain"));
+    }
+
+    #[tokio::test]
+    async fn test_push_invalid_attributes() {
+        let app = init_application!(&CONFIG);
+
+        let push = test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                attributes: Some(vec![("".into(), "value".into())].into_iter().collect()),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        assert_eq!(
+            *push.body(),
+            Bytes::from_static(b"Unable to compose message")
+        );
+
+        let pop = test_request!(app, "GET", "/test").await;
+        assert_eq!(*pop.body(), Bytes::from_static(b"No message available"));
+    }
+
+    #[tokio::test]
+    async fn test_priority_push() {
+        let app = init_application!(&CONFIG);
+
+        for (body, priority) in &[("Low", None), ("High", Some(10)), ("Medium", Some(5))] {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    priority: *priority,
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        for body in &["High", "Medium", "Low"] {
+            let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+            assert_eq!(&*pop.body, *body);
+        }
+    }
+
     #[tokio::test]
     async fn test_delayed_push() {
         let app = init_application!(&CONFIG);
@@ -77,4 +180,204 @@ mod tests {
         let pop = test_request!(app, "GET", "/test").await;
         assert_eq!(*pop.body(), Bytes::from_static(b"No message available"));
     }
+
+    #[tokio::test]
+    async fn test_deduplicated_push() {
+        let app = init_application!(&CONFIG);
+
+        let mut ids = Vec::new();
+
+        for (body, deduplication_id) in
+            &[("Hello", "hello"), ("world", "hello"), ("Other", "other")]
+        {
+            let push: PushResponse = test_json_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    deduplication_id: Some(String::from(*deduplication_id).into_boxed_str()),
+                    ..Default::default()
+                }
+            );
+
+            ids.push(push.id);
+        }
+
+        assert_eq!(ids[0], ids[1]);
+        assert_ne!(ids[0], ids[2]);
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 2);
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(pop.id, ids[0]);
+        assert_eq!(&*pop.body, "Hello");
+    }
+
+    #[tokio::test]
+    async fn test_push_with_id() {
+        let app = init_application!(&CONFIG);
+
+        let request = PushRequest {
+            id: Some(Uuid::new_v4()),
+            body: String::from("Hello, world").into_boxed_str(),
+            ..Default::default()
+        };
+
+        let push: PushResponse = test_json_request!(app, "POST", "/test", &request);
+        assert_eq!(Some(push.id), request.id);
+
+        let response = test_request!(app, "POST", "/test", &request).await;
+        assert_eq!(response.status(), 409);
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 1);
+    }
+
+    #[tokio::test]
+    async fn test_timezone_push() {
+        let app = init_application!(&CONFIG);
+
+        let push = test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                timezone: Some(String::from("Unknown/Zone").into_boxed_str()),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        assert_eq!(
+            *push.body(),
+            Bytes::from_static(b"Unable to compose message")
+        );
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                timezone: Some(String::from("Europe/Berlin").into_boxed_str()),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: Value = test_json_request!(app, "GET", "/test");
+        assert_eq!(pop["time"]["timezone"], "Europe/Berlin");
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_push() {
+        let app = init_application!(&CONFIG);
+
+        let deliver_at =
+            Utc::now().with_timezone(&FixedOffset::east_opt(3600).unwrap()) + Duration::hours(1);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                deliver_at: Some(DeliveryTime::Absolute(deliver_at)),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop = test_request!(app, "GET", "/test").await;
+        assert_eq!(*pop.body(), Bytes::from_static(b"No message available"));
+
+        let push = test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                deliver_at: Some(DeliveryTime::Absolute(deliver_at - Duration::hours(2))),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        assert_eq!(
+            *push.body(),
+            Bytes::from_static(b"Unable to compose message")
+        );
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                deliver_at: Some(DeliveryTime::Absolute(deliver_at - Duration::hours(2))),
+                allow_past_delivery: true,
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(&*pop.body, "Hello, world");
+    }
+
+    #[tokio::test]
+    async fn test_push_delay_with_delivery_time() {
+        let app = init_application!(&CONFIG);
+
+        let deliver_at =
+            Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap()) + Duration::hours(1);
+
+        let push = test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                delay: Some(60),
+                deliver_at: Some(DeliveryTime::Absolute(deliver_at)),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        assert_eq!(
+            *push.body(),
+            Bytes::from_static(b"Unable to compose message")
+        );
+
+        let pop = test_request!(app, "GET", "/test").await;
+        assert_eq!(*pop.body(), Bytes::from_static(b"No message available"));
+    }
+
+    #[tokio::test]
+    async fn test_local_scheduled_push() {
+        let app = init_application!(&CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                timezone: Some(String::from("Europe/Berlin").into_boxed_str()),
+                deliver_at: Some(DeliveryTime::Local("2030-07-15T09:00:00".parse().unwrap())),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let page: Value = test_json_request!(app, "GET", "/test/messages");
+        assert_eq!(
+            page["messages"][0]["time"]["delay"],
+            "2030-07-15T09:00:00+02:00"
+        );
+    }
 }
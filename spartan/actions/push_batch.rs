@@ -0,0 +1,152 @@
+use std::{convert::TryInto, sync::Arc};
+
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::{QueueError, Result},
+    http::query::{batch::BatchResponse, push::PushRequest},
+    node::Manager,
+};
+
+/// Max amount of messages in single pushed batch
+const MAX_BATCH_SIZE: usize = 1000;
+
+/// Push batch of messages to queue.
+///
+/// Requires array of messages, each with the same fields as single push. Returns IDs of messages in batch order.
+///
+/// Batch of more than 1000 messages is rejected.
+///
+/// All messages are validated before any of them is pushed, so invalid message or conflicting ID rejects whole batch.
+///
+/// Messages are pushed under single queue lock, and whole batch is logged as single event.
+///
+/// Messages, discarded by deduplication ID, get ID of original message.
+pub async fn push_batch(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    requests: Vec<PushRequest>,
+) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+    let ttl = manager.queue_config(&name).and_then(|config| config.ttl);
+
+    if requests.len() > MAX_BATCH_SIZE {
+        return Err(QueueError::BatchTooLarge.into());
+    }
+
+    let messages = requests
+        .into_iter()
+        .map(|mut request| {
+            if request.ttl.is_none() {
+                request.ttl = ttl;
+            }
+
+            let deduplication_id = request.deduplication_id.take();
+
+            Ok((request.try_into()?, deduplication_id))
+        })
+        .collect::<std::result::Result<Vec<_>, QueueError>>()?;
+
+    let ids = queue.push_batch(&name, &manager, messages).await?;
+
+    Ok(json(&BatchResponse::from(ids)))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use spartan_lib::uuid::Uuid;
+
+    use warp::hyper::StatusCode;
+
+    use super::MAX_BATCH_SIZE;
+    use crate::{
+        http::query::{batch::BatchResponse, push::PushRequest, size::SizeResponse},
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    fn request(body: &str, deduplication_id: Option<&str>) -> PushRequest {
+        PushRequest {
+            body: String::from(body).into_boxed_str(),
+            deduplication_id: deduplication_id.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_push_batch() {
+        let app = init_application!(&CONFIG);
+
+        let push: BatchResponse = test_json_request!(
+            app,
+            "POST",
+            "/test/batch",
+            &[
+                request("First", Some("first")),
+                request("Second", None),
+                request("Repeated", Some("first")),
+            ]
+        );
+
+        assert_eq!(push.ids.len(), 3);
+        assert_eq!(push.ids[0], push.ids[2]);
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 2);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_push_batch() {
+        let app = init_application!(&CONFIG);
+
+        let resp = test_request!(
+            app,
+            "POST",
+            "/test/batch",
+            &[
+                request("First", None),
+                PushRequest {
+                    timezone: Some("Mars/Olympus_Mons".into()),
+                    ..request("Second", None)
+                },
+            ]
+        )
+        .await;
+        assert_eq!(
+            *resp.body(),
+            Bytes::from_static(b"Unable to compose message")
+        );
+
+        let id = Uuid::new_v4();
+        let resp = test_request!(
+            app,
+            "POST",
+            "/test/batch",
+            &[
+                PushRequest {
+                    id: Some(id),
+                    ..request("First", None)
+                },
+                PushRequest {
+                    id: Some(id),
+                    ..request("Second", None)
+                },
+            ]
+        )
+        .await;
+        assert_eq!(
+            *resp.body(),
+            Bytes::from_static(b"Message with the same ID already exists")
+        );
+
+        let requests: Vec<_> = (0..=MAX_BATCH_SIZE)
+            .map(|_| request("Hello", None))
+            .collect();
+        let resp = test_request!(app, "POST", "/test/batch", &requests).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 0);
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::{QueueError, Result},
+    http::query::push::{PushResponse, RawPushQuery},
+    node::Manager,
+};
+
+/// Push raw `application/octet-stream` message to queue.
+///
+/// Request body is used as message body as-is. ID, offset, timezone, max tries, timeout, delay, delivery time, priority, TTL, deduplication ID and group ID are optional query parameters.
+///
+/// Attributes can't be provided, use JSON push for messages with attributes.
+///
+/// If TTL is not provided, then queue default TTL is used.
+///
+/// If deduplication ID is provided, then repeated push with the same ID within queue deduplication window is discarded.
+///
+/// Push with ID, that already exists in queue, is rejected with conflict error.
+///
+/// Returns ID of pushed message, or ID of original message, if push was discarded.
+pub async fn push_raw(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    mut query: RawPushQuery,
+    body: Bytes,
+) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+
+    if query.ttl.is_none() {
+        query.ttl = manager.queue_config(&name).and_then(|config| config.ttl);
+    }
+
+    let deduplication_id = query.deduplication_id.take();
+    let message = query.compose(body).map_err(QueueError::MessageCompose)?;
+
+    let id = queue
+        .push(&name, &manager, message, deduplication_id)
+        .await?;
+
+    Ok(json(&PushResponse { id }))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use serde_json::Value;
+
+    use crate::{
+        http::query::{push::PushResponse, size::SizeResponse},
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_raw_push() {
+        let app = init_application!(&CONFIG);
+
+        let push = ::warp::test::request()
+            .method("POST")
+            .path("/test?max_tries=2")
+            .header("content-type", "application/octet-stream")
+            .body([0u8, 159, 146, 150])
+            .reply(&app)
+            .await;
+
+        let push: PushResponse = serde_json::from_slice(push.body()).unwrap();
+
+        let pop = ::warp::test::request()
+            .method("GET")
+            .path("/test")
+            .header("accept", "application/octet-stream")
+            .reply(&app)
+            .await;
+
+        assert_eq!(*pop.body(), Bytes::from_static(&[0, 159, 146, 150]));
+        assert_eq!(pop.headers()["x-message-id"], push.id.to_string());
+        assert_eq!(pop.headers()["x-message-tries"], "1");
+        assert_eq!(pop.headers()["x-message-max-tries"], "2");
+
+        let pop = test_request!(app, "GET", "/test").await;
+        assert_eq!(*pop.body(), Bytes::from_static(b"No message available"));
+    }
+
+    #[tokio::test]
+    async fn test_raw_push_json_pop() {
+        let app = init_application!(&CONFIG);
+
+        ::warp::test::request()
+            .method("POST")
+            .path("/test")
+            .header("content-type", "application/octet-stream")
+            .body([0u8, 159, 146, 150])
+            .reply(&app)
+            .await;
+
+        let pop: Value = test_json_request!(app, "GET", "/test");
+
+        assert_eq!(pop["body"], "AJ+Slg==");
+        assert_eq!(pop["encoding"], "base64");
+    }
+
+    #[tokio::test]
+    async fn test_raw_push_deduplication() {
+        let app = init_application!(&CONFIG);
+
+        let mut ids = Vec::new();
+
+        for body in &["Hello", "world"] {
+            let push = ::warp::test::request()
+                .method("POST")
+                .path("/test?deduplication_id=hello")
+                .header("content-type", "application/octet-stream")
+                .body(*body)
+                .reply(&app)
+                .await;
+
+            let push: PushResponse = serde_json::from_slice(push.body()).unwrap();
+            ids.push(push.id);
+        }
+
+        assert_eq!(ids[0], ids[1]);
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 1);
+    }
+
+    #[tokio::test]
+    async fn test_raw_scheduled_push() {
+        let app = init_application!(&CONFIG);
+
+        let push = ::warp::test::request()
+            .method("POST")
+            .path("/test?deliver_at=2000-01-01T00:00:00Z")
+            .header("content-type", "application/octet-stream")
+            .body("Hello, world")
+            .reply(&app)
+            .await;
+
+        assert_eq!(
+            *push.body(),
+            Bytes::from_static(b"Unable to compose message")
+        );
+
+        ::warp::test::request()
+            .method("POST")
+            .path("/test?deliver_at=2000-01-01T00:00:00Z&allow_past_delivery=true")
+            .header("content-type", "application/octet-stream")
+            .body("Hello, world")
+            .reply(&app)
+            .await;
+
+        ::warp::test::request()
+            .method("POST")
+            .path("/test?deliver_at=2100-01-01T00:00:00")
+            .header("content-type", "application/octet-stream")
+            .body("Scheduled")
+            .reply(&app)
+            .await;
+
+        let pop: Value = test_json_request!(app, "GET", "/test");
+        assert_eq!(pop["body"], "Hello, world");
+
+        let pop = test_request!(app, "GET", "/test").await;
+        assert_eq!(*pop.body(), Bytes::from_static(b"No message available"));
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 2);
+    }
+}
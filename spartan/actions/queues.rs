@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::Result,
+    http::query::queues::{QueueEntry, QueuesResponse},
+    node::Manager,
+};
+
+/// List queues of node.
+///
+/// Doesn't require any input, returns names of queues, sorted alphabetically,
+/// and whether each queue was created at runtime.
+pub async fn queues(manager: Arc<Manager<'_>>) -> Result<Json> {
+    let runtime = manager.queues().await;
+
+    let mut queues = manager
+        .node()
+        .iter()
+        .map(|(name, _)| QueueEntry {
+            runtime: runtime.contains(&*name),
+            name: name.as_ref().into(),
+        })
+        .collect::<Vec<_>>();
+
+    queues.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(json(&QueuesResponse { queues }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::query::queues::QueuesResponse, init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_queues() {
+        let app = init_application!(&CONFIG);
+
+        let response: QueuesResponse = test_json_request!(app, "GET", "/queues");
+
+        assert_eq!(response.queues.len(), 2);
+        assert_eq!(&*response.queues[0].name, "test");
+        assert_eq!(&*response.queues[1].name, "test_2");
+        assert!(response.queues.iter().all(|queue| !queue.runtime));
+    }
+}
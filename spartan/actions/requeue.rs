@@ -1,35 +1,106 @@
 use std::sync::Arc;
 
-use spartan_lib::core::dispatcher::StatusAwareDispatcher;
+use chrono::Utc;
+use spartan_lib::core::{
+    db::Database,
+    dispatcher::StatusAwareDispatcher,
+    message::Message,
+    payload::{Dispatchable, Identifiable, Status},
+};
 use warp::reply::{json, Json};
 
 use crate::{
     actions::{QueueError, Result},
     http::query::requeue::RequeueRequest,
-    node::{event::Event, Manager},
+    node::{counters::Counter, event::Event, Manager},
 };
 
+/// Requeue message back to queue, optionally recording failure reason
+///
+/// If delay is not provided and queue has retry policy, then message is delayed according to it.
+///
+/// Message with exhausted tries is moved to dead letter queue, if there is any.
+pub(crate) async fn requeue_message(
+    manager: &Manager<'_>,
+    name: &str,
+    id: <Message as Identifiable>::Id,
+    reason: Option<Box<str>>,
+    delay: Option<u32>,
+) -> Result<()> {
+    let queue = manager.queue(name)?;
+    let retry = manager
+        .queue_config(name)
+        .and_then(|config| config.retry.as_ref());
+
+    let now = Utc::now().timestamp();
+
+    // Tries are read under the same lock, so delay matches reservation being requeued
+    let (tries, available_at) = {
+        let database = queue.database().await;
+
+        let message = database
+            .get(id)
+            .filter(|message| message.requeueable() && message.obtainable())
+            .ok_or(QueueError::MessageNotFound)?;
+
+        let tries = *message.state().tries();
+
+        let available_at = delay
+            .or_else(|| retry.map(|retry| retry.delay(tries)))
+            .map(|delay| now + i64::from(delay));
+
+        (tries, available_at)
+    };
+
+    let event = match (&reason, available_at) {
+        (Some(reason), available_at) => Event::Nack(id, Some(reason.clone()), available_at, now),
+        (None, Some(available_at)) => Event::RequeueDelayed(id, available_at, now),
+        (None, None) => Event::Requeue(id, now),
+    };
+
+    // Database is not locked while event is logged, so message is checked again,
+    // in case it was requeued, deleted or reserved once more meanwhile
+    queue.log_event(name, manager, event).await?;
+
+    let exhausted = {
+        let mut database = queue.database().await;
+
+        if !matches!(database.get(id), Some(message) if *message.state().tries() == tries) {
+            return Err(QueueError::MessageNotFound.into());
+        }
+
+        database
+            .nack(id, reason, available_at, now)
+            .ok_or(QueueError::MessageNotFound)?;
+
+        queue.counters().add(Counter::Requeue, 1);
+
+        manager.dead_letter_queue(name).is_some()
+            && matches!(database.get(id), Some(message) if !message.has_tries())
+    };
+
+    if exhausted {
+        manager.dead_letter(name, &queue, vec![id]).await?;
+    }
+
+    Ok(())
+}
+
 /// Requeues message back to queue.
 ///
 /// Requires ID of message being requeued, returns empty response.
 ///
 /// Message try counter is incremented.
+///
+/// If queue has retry policy, then message is delayed according to it.
+///
+/// Message with exhausted tries is moved to dead letter queue, if there is any.
 pub async fn requeue(
     manager: Arc<Manager<'_>>,
     name: String,
     request: RequeueRequest,
 ) -> Result<Json> {
-    let queue = manager.queue(&name)?;
-
-    queue
-        .log_event(&name, &manager, Event::Requeue(request.id))
-        .await?;
-
-    queue
-        .database()
-        .await
-        .requeue(request.id)
-        .ok_or(QueueError::MessageNotFound)?;
+    requeue_message(&manager, &name, request.id, None, None).await?;
 
     Ok(json(&()))
 }
@@ -37,16 +108,35 @@ pub async fn requeue(
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
+    use once_cell::sync::Lazy;
     use uuid::Uuid;
 
     use crate::{
+        config::{
+            queue::{Backoff, QueueConfig, RetryPolicy},
+            Config,
+        },
         http::query::{
             pop::test_response::TestPopResponse, push::PushRequest, requeue::RequeueRequest,
+            size::SizeResponse,
         },
         init_application, test_json_request, test_request,
         utils::testing::CONFIG,
     };
 
+    static DEAD_LETTER_CONFIG: Lazy<Config> = Lazy::new(|| Config {
+        queue_config: vec![(
+            String::from("test").into_boxed_str(),
+            QueueConfig {
+                dead_letter: Some(String::from("test_2").into_boxed_str()),
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    });
+
     #[tokio::test]
     async fn test_empty_requeue() {
         let app = init_application!(&CONFIG);
@@ -106,4 +196,80 @@ mod tests {
             Bytes::from_static(b"No message available")
         );
     }
+
+    static RETRY_CONFIG: Lazy<Config> = Lazy::new(|| Config {
+        queue_config: vec![(
+            String::from("test").into_boxed_str(),
+            QueueConfig {
+                retry: Some(RetryPolicy {
+                    backoff: Backoff::Exponential,
+                    delay: 600,
+                    max_delay: None,
+                    jitter: 0,
+                }),
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    });
+
+    #[tokio::test]
+    async fn test_retry_policy_requeue() {
+        let app = init_application!(&RETRY_CONFIG);
+
+        for body in &["First", "Second"] {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    max_tries: Some(2),
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(&*pop.body, "First");
+
+        let requeue =
+            test_request!(app, "POST", "/test/requeue", &RequeueRequest { id: pop.id }).await;
+        assert_eq!(*requeue.body(), Bytes::from_static(b"null"));
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(&*pop.body, "Second");
+
+        let empty = test_request!(app, "GET", "/test").await;
+        assert_eq!(*empty.body(), Bytes::from_static(b"No message available"));
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_requeue() {
+        let app = init_application!(&DEAD_LETTER_CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                ..Default::default()
+            }
+        )
+        .await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+
+        test_request!(app, "POST", "/test/requeue", &RequeueRequest { id: pop.id }).await;
+
+        let size: SizeResponse = test_json_request!(app, "GET", "/test/size");
+        assert_eq!(size.size, 0);
+
+        let dead_pop: TestPopResponse = test_json_request!(app, "GET", "/test_2");
+        assert_eq!(dead_pop.id, pop.id);
+    }
 }
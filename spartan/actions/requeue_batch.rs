@@ -0,0 +1,158 @@
+use std::{collections::HashSet, sync::Arc};
+
+use chrono::Utc;
+use spartan_lib::core::{
+    db::Database,
+    dispatcher::StatusAwareDispatcher,
+    payload::{Dispatchable, Status},
+};
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::Result,
+    http::query::batch::{BatchRequest, BatchResponse},
+    node::{counters::Counter, event::Event, Manager},
+};
+
+/// Requeue multiple messages back to queue.
+///
+/// Requires IDs of messages being requeued, returns IDs of requeued messages.
+///
+/// Messages, that were not found or are not reserved, are skipped, as well as repeated IDs.
+/// Whole batch is logged as single event.
+///
+/// If queue has retry policy, then each message is delayed according to it.
+///
+/// Messages with exhausted tries are moved to dead letter queue, if there is any.
+pub async fn requeue_batch(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    request: BatchRequest,
+) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+    let retry = manager
+        .queue_config(&name)
+        .and_then(|config| config.retry.as_ref());
+
+    let now = Utc::now().timestamp();
+
+    let requeues: Vec<_> = {
+        let database = queue.database().await;
+        let mut seen = HashSet::new();
+
+        request
+            .ids
+            .into_iter()
+            .filter(|id| seen.insert(*id))
+            .filter_map(|id| {
+                let message = database
+                    .get(id)
+                    .filter(|message| message.requeueable() && message.obtainable())?;
+
+                let available_at =
+                    retry.map(|retry| now + i64::from(retry.delay(*message.state().tries())));
+
+                Some((id, available_at))
+            })
+            .collect()
+    };
+
+    if requeues.is_empty() {
+        return Ok(json(&BatchResponse::from(Vec::new())));
+    }
+
+    // Database is not locked while batch is logged, so messages,
+    // that were requeued or deleted meanwhile, are skipped when batch is applied
+    queue
+        .log_event(&name, &manager, Event::RequeueBatch(requeues.clone(), now))
+        .await?;
+
+    let (ids, exhausted) = {
+        let mut database = queue.database().await;
+
+        let mut ids = Vec::with_capacity(requeues.len());
+        let mut exhausted = Vec::new();
+
+        for (id, available_at) in requeues {
+            if database.nack(id, None, available_at, now).is_none() {
+                continue;
+            }
+
+            ids.push(id);
+
+            if manager.dead_letter_queue(&name).is_some()
+                && matches!(database.get(id), Some(message) if !message.has_tries())
+            {
+                exhausted.push(id);
+            }
+        }
+
+        queue.counters().add(Counter::Requeue, ids.len());
+
+        (ids, exhausted)
+    };
+
+    if !exhausted.is_empty() {
+        manager.dead_letter(&name, &queue, exhausted).await?;
+    }
+
+    Ok(json(&BatchResponse::from(ids)))
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::{
+        http::query::{
+            batch::{test_response::TestPopBatchResponse, BatchRequest, BatchResponse},
+            push::PushRequest,
+        },
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_requeue_batch() {
+        let app = init_application!(&CONFIG);
+
+        for body in &["First", "Second"] {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from(*body).into_boxed_str(),
+                    max_tries: Some(2),
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        let pop: TestPopBatchResponse = test_json_request!(app, "GET", "/test/batch?count=2");
+        let mut ids: Vec<_> = pop.messages.iter().map(|message| message.id).collect();
+        ids.push(Uuid::new_v4());
+        ids.push(ids[0]);
+
+        let requeue: BatchResponse = test_json_request!(
+            app,
+            "POST",
+            "/test/requeue/batch",
+            &BatchRequest { ids: ids.clone() }
+        );
+        assert_eq!(requeue.ids, &ids[..2]);
+
+        // Already requeued messages are skipped
+        let requeue: BatchResponse = test_json_request!(
+            app,
+            "POST",
+            "/test/requeue/batch",
+            &BatchRequest { ids: ids.clone() }
+        );
+        assert!(requeue.ids.is_empty());
+
+        let pop: TestPopBatchResponse = test_json_request!(app, "GET", "/test/batch?count=2");
+        assert_eq!(pop.messages.len(), 2);
+    }
+}
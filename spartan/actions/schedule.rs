@@ -0,0 +1,98 @@
+use std::{convert::TryFrom, sync::Arc};
+
+use spartan_lib::core::message::Message;
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::{QueueError, Result},
+    http::query::schedule::{ScheduleRequest, ScheduleResponse},
+    node::{schedule::Schedule, Manager},
+};
+
+/// Create recurring message schedule.
+///
+/// Requires schedule name, pushed message and either cron expression with seconds field, or interval in seconds.
+/// Existing schedule with the same name is replaced.
+///
+/// Message deduplication ID is ignored, as each run of schedule is deduplicated on its own.
+///
+/// Returns created schedule with its next run time.
+pub async fn schedule(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    request: ScheduleRequest,
+) -> Result<Json> {
+    manager.queue(&name)?;
+
+    Message::try_from(request.message.clone()).map_err(QueueError::MessageCompose)?;
+
+    let schedule = Schedule::new(request.into_config(name), false)?;
+
+    let response = json(&ScheduleResponse::from(&schedule));
+
+    manager.schedules().await.insert(schedule);
+    manager.persist_schedules().await?;
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::{
+        http::query::{
+            push::PushRequest,
+            schedule::{test_response::TestSchedulesResponse, ScheduleRequest},
+        },
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_schedule() {
+        let app = init_application!(&CONFIG);
+
+        let request = ScheduleRequest {
+            name: "test".into(),
+            cron: Some("0 0 * * * *".into()),
+            interval: None,
+            message: PushRequest {
+                body: "Hello, world".into(),
+                ..Default::default()
+            },
+        };
+
+        let response = test_request!(app, "POST", "/test/schedules", &request).await;
+        assert_eq!(response.status(), 200);
+
+        let schedules: TestSchedulesResponse = test_json_request!(app, "GET", "/test/schedules");
+        assert_eq!(schedules.schedules.len(), 1);
+        assert_eq!(&*schedules.schedules[0].name, "test");
+        assert!(schedules.schedules[0].next_run > Utc::now());
+
+        let schedules: TestSchedulesResponse = test_json_request!(app, "GET", "/test_2/schedules");
+        assert!(schedules.schedules.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_schedule() {
+        let app = init_application!(&CONFIG);
+
+        let request = ScheduleRequest {
+            name: "test".into(),
+            cron: Some("0 0 * * * *".into()),
+            interval: Some(60),
+            message: PushRequest {
+                body: "Hello, world".into(),
+                ..Default::default()
+            },
+        };
+
+        let response = test_request!(app, "POST", "/test/schedules", &request).await;
+        assert_eq!(response.status(), 400);
+
+        let response = test_request!(app, "POST", "/unknown/schedules", &request).await;
+        assert_eq!(response.status(), 404);
+    }
+}
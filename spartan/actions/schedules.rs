@@ -0,0 +1,16 @@
+use std::sync::Arc;
+
+use warp::reply::{json, Json};
+
+use crate::{actions::Result, http::query::schedule::SchedulesResponse, node::Manager};
+
+/// List recurring message schedules of queue.
+///
+/// Doesn't require any input, returns schedules with their next run times.
+pub async fn schedules(manager: Arc<Manager<'_>>, name: String) -> Result<Json> {
+    manager.queue(&name)?;
+
+    let schedules = manager.schedules().await;
+
+    Ok(json(&SchedulesResponse::from(schedules.queue(&name))))
+}
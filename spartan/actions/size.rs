@@ -9,9 +9,10 @@ use crate::{actions::Result, http::query::size::SizeResponse, node::Manager};
 ///
 /// Doesn't require any input, returns queue size.
 pub async fn size(manager: Arc<Manager<'_>>, name: String) -> Result<Json> {
-    let queue = manager.queue(&name)?.database().await;
+    let queue = manager.queue(&name)?;
+    let size = queue.database().await.size();
 
-    Ok(json(&SizeResponse::from(queue.size())))
+    Ok(json(&SizeResponse::from(size)))
 }
 
 #[cfg(test)]
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use spartan_lib::core::db::{Database, StatusAwareDatabase};
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::Result,
+    http::query::stats::{Messages, StatsResponse, Totals},
+    node::Manager,
+};
+
+/// Get queue statistics.
+///
+/// Doesn't require any input, returns amount of messages in each state,
+/// age of the oldest message and totals of queue operations since start.
+///
+/// Message states are counted by database, so queue is not scanned.
+pub async fn stats(manager: Arc<Manager<'_>>, name: String) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+    let now = Utc::now().timestamp();
+
+    let (size, counts) = {
+        let database = queue.database().await;
+        (database.len(), database.status_counts(now))
+    };
+
+    Ok(json(&StatsResponse {
+        size,
+        messages: Messages::from(&counts),
+        oldest_message_age: counts.oldest.map(|oldest| now - oldest),
+        totals: Totals::from(queue.counters()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::query::{
+            delete::DeleteRequest, pop::test_response::TestPopResponse, push::PushRequest,
+            requeue::RequeueRequest, stats::StatsResponse,
+        },
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_stats() {
+        let app = init_application!(&CONFIG);
+
+        let stats: StatsResponse = test_json_request!(app, "GET", "/test/stats");
+        assert_eq!(stats.size, 0);
+        assert!(stats.oldest_message_age.is_none());
+
+        for delay in &[None, None, None, Some(600)] {
+            test_request!(
+                app,
+                "POST",
+                "/test",
+                &PushRequest {
+                    body: String::from("Hello, world").into_boxed_str(),
+                    max_tries: Some(2),
+                    delay: *delay,
+                    ..Default::default()
+                }
+            )
+            .await;
+        }
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        test_request!(app, "POST", "/test/requeue", &RequeueRequest { id: pop.id }).await;
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        test_request!(app, "DELETE", "/test", &DeleteRequest { id: pop.id }).await;
+
+        test_request!(app, "GET", "/test").await;
+
+        let stats: StatsResponse = test_json_request!(app, "GET", "/test/stats");
+        assert_eq!(stats.size, 3);
+        assert_eq!(stats.messages.available, 1);
+        assert_eq!(stats.messages.transit, 1);
+        assert_eq!(stats.messages.delayed, 1);
+        assert_eq!(stats.messages.exhausted, 0);
+        assert_eq!(stats.messages.expired, 0);
+        assert!(stats.oldest_message_age.unwrap() >= 0);
+        assert_eq!(stats.totals.pushes, 4);
+        assert_eq!(stats.totals.pops, 3);
+        assert_eq!(stats.totals.requeues, 1);
+        assert_eq!(stats.totals.deletes, 1);
+        assert_eq!(stats.totals.gc_removals, 0);
+    }
+}
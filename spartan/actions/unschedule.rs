@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::Result,
+    http::query::schedule::{ScheduleResponse, UnscheduleRequest},
+    node::Manager,
+};
+
+/// Delete recurring message schedule.
+///
+/// Requires schedule name, returns deleted schedule.
+///
+/// Schedules, defined in server config, are restored on restart.
+pub async fn unschedule(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    request: UnscheduleRequest,
+) -> Result<Json> {
+    manager.queue(&name)?;
+
+    let schedule = manager.schedules().await.remove(&name, &request.name)?;
+    manager.persist_schedules().await?;
+
+    Ok(json(&ScheduleResponse::from(&schedule)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::query::{
+            push::PushRequest,
+            schedule::{test_response::TestSchedulesResponse, ScheduleRequest, UnscheduleRequest},
+        },
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_unschedule() {
+        let app = init_application!(&CONFIG);
+
+        test_request!(
+            app,
+            "POST",
+            "/test/schedules",
+            &ScheduleRequest {
+                name: "test".into(),
+                cron: None,
+                interval: Some(60),
+                message: PushRequest {
+                    body: "Hello, world".into(),
+                    ..Default::default()
+                },
+            }
+        )
+        .await;
+
+        let request = UnscheduleRequest {
+            name: "test".into(),
+        };
+
+        let response = test_request!(app, "DELETE", "/test/schedules", &request).await;
+        assert_eq!(response.status(), 200);
+
+        let schedules: TestSchedulesResponse = test_json_request!(app, "GET", "/test/schedules");
+        assert!(schedules.schedules.is_empty());
+
+        let response = test_request!(app, "DELETE", "/test/schedules", &request).await;
+        assert_eq!(response.status(), 404);
+    }
+}
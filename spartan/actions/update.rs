@@ -0,0 +1,164 @@
+use std::{result::Result as StdResult, sync::Arc};
+
+use chrono::Utc;
+use spartan_lib::{
+    core::{db::Database, dispatcher::StatusAwareDispatcher, message::Message, payload::Status},
+    uuid::Uuid,
+};
+use warp::reply::{json, Json};
+
+use crate::{
+    actions::{QueueError, Result},
+    http::query::{pop::PopResponse, update::UpdateRequest},
+    node::{event::Event, Manager},
+};
+
+/// Check, that message exists and is not reserved
+fn updatable(message: Option<&Message>) -> StdResult<(), QueueError> {
+    match message {
+        Some(message) if message.requeueable() => Err(QueueError::MessageReserved),
+        Some(_) => Ok(()),
+        None => Err(QueueError::MessageNotFound),
+    }
+}
+
+/// Update message, that is currently not being processed.
+///
+/// Requires ID of message in path, and new body, delay, max tries or timeout. Returns updated message.
+///
+/// Message keeps its ID and tries. Delay is counted from the time of update. Max tries must be greater than zero.
+///
+/// Reserved message is rejected with conflict status.
+pub async fn update(
+    manager: Arc<Manager<'_>>,
+    name: String,
+    id: Uuid,
+    request: UpdateRequest,
+) -> Result<Json> {
+    let queue = manager.queue(&name)?;
+
+    if request.max_tries == Some(0) {
+        return Err(QueueError::InvalidMaxTries.into());
+    }
+
+    let patch = request.into_patch(Utc::now().timestamp());
+
+    updatable(queue.database().await.get(id))?;
+
+    queue
+        .log_event(&name, &manager, Event::Update(id, patch.clone()))
+        .await?;
+
+    let mut database = queue.database().await;
+
+    // Message may be reserved or deleted, while update is logged
+    updatable(database.get(id))?;
+
+    database
+        .update(id, |message| message.apply(patch))
+        .ok_or(QueueError::MessageNotFound)?;
+
+    let message = database.get(id).ok_or(QueueError::MessageNotFound)?;
+
+    Ok(json(&PopResponse::from(message)))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use uuid::Uuid;
+    use warp::hyper::StatusCode;
+
+    use crate::{
+        http::query::{
+            pop::test_response::TestPopResponse,
+            push::{PushRequest, PushResponse},
+            update::UpdateRequest,
+        },
+        init_application, test_json_request, test_request,
+        utils::testing::CONFIG,
+    };
+
+    #[tokio::test]
+    async fn test_empty_update() {
+        let app = init_application!(&CONFIG);
+        let resp = test_request!(
+            app,
+            "PATCH",
+            &format!("/test/messages/{}", Uuid::new_v4()),
+            &UpdateRequest::default()
+        )
+        .await;
+
+        assert_eq!(*resp.body(), Bytes::from_static(b"Message not found"));
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let app = init_application!(&CONFIG);
+
+        let push: PushResponse = test_json_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                delay: Some(600),
+                ..Default::default()
+            }
+        );
+
+        let uri = format!("/test/messages/{}", push.id);
+
+        let message: TestPopResponse = test_json_request!(
+            app,
+            "PATCH",
+            &uri,
+            &UpdateRequest {
+                body: Some(String::from("Updated").into_boxed_str()),
+                delay: Some(0),
+                ..Default::default()
+            }
+        );
+        assert_eq!(message.id, push.id);
+        assert_eq!(&*message.body, "Updated");
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(pop.id, push.id);
+        assert_eq!(&*pop.body, "Updated");
+
+        let resp = test_request!(app, "PATCH", &uri, &UpdateRequest::default()).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        assert_eq!(*resp.body(), Bytes::from_static(b"Message is reserved"));
+    }
+
+    #[tokio::test]
+    async fn test_update_zero_max_tries() {
+        let app = init_application!(&CONFIG);
+
+        let push: PushResponse = test_json_request!(
+            app,
+            "POST",
+            "/test",
+            &PushRequest {
+                body: String::from("Hello, world").into_boxed_str(),
+                ..Default::default()
+            }
+        );
+
+        let resp = test_request!(
+            app,
+            "PATCH",
+            &format!("/test/messages/{}", push.id),
+            &UpdateRequest {
+                max_tries: Some(0),
+                ..Default::default()
+            }
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let pop: TestPopResponse = test_json_request!(app, "GET", "/test");
+        assert_eq!(pop.id, push.id);
+    }
+}
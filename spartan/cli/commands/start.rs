@@ -9,7 +9,10 @@ use crate::{
     cli::Server,
     dispatch_jobs,
     http::server::{start_http_server, ServerError},
-    jobs::{gc::spawn_gc, persistence::spawn_persistence},
+    jobs::{
+        gc::spawn_gc, persistence::spawn_persistence, reclaim::spawn_reclaim,
+        schedule::spawn_schedules,
+    },
     node::{persistence::PersistenceError, Manager},
 };
 
@@ -53,7 +56,13 @@ impl StartCommand {
 
         let manager = Arc::new(manager);
 
-        dispatch_jobs!(manager, spawn_gc, spawn_persistence);
+        dispatch_jobs!(
+            manager,
+            spawn_gc,
+            spawn_persistence,
+            spawn_reclaim,
+            spawn_schedules
+        );
 
         #[cfg(feature = "replication")]
         dispatch_jobs!(manager, spawn_replication);
@@ -7,7 +7,7 @@ use std::{
 use serde::{Deserialize, Serialize};
 
 /// Wildcard queue name. Used in `queues` to represent all available queues in node
-const WILDCARD_QUEUE: &str = "*";
+pub(crate) const WILDCARD_QUEUE: &str = "*";
 
 /// Single access key
 ///
@@ -7,18 +7,34 @@ pub mod replication;
 /// Persistence config
 pub mod persistence;
 
-use std::collections::HashSet;
+/// Queue config
+pub mod queue;
+
+/// Recurring message schedule config
+pub mod schedule;
+
+use std::collections::{HashMap, HashSet};
 
 use key::Key;
 use persistence::PersistenceConfig;
+use queue::QueueConfig;
 use replication::ReplicationConfig;
-use serde::{Deserialize, Serialize, Serializer};
+use schedule::ScheduleConfig;
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Queue names, that are used by node-level routes
+pub const RESERVED_QUEUE_NAMES: [&str; 2] = ["metrics", "queues"];
 
 /// Default amount of seconds between GC jobs
 const fn default_gc_timer() -> u64 {
     300
 }
 
+/// Default amount of seconds between timed out reservation checks
+const fn default_reclaim_timer() -> u64 {
+    1
+}
+
 fn default_persistence() -> PersistenceConfig<'static> {
     PersistenceConfig::default()
 }
@@ -37,6 +53,44 @@ where
     }
 }
 
+fn deserialize_queues<'de, D>(deserializer: D) -> Result<Box<[Box<str>]>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let queues = Box::<[Box<str>]>::deserialize(deserializer)?;
+
+    match queues
+        .iter()
+        .find(|queue| RESERVED_QUEUE_NAMES.contains(&&***queue))
+    {
+        Some(queue) => Err(D::Error::custom(format!(
+            "queue name \"{}\" is reserved",
+            queue
+        ))),
+        None => Ok(queues),
+    }
+}
+
+fn deserialize_queue_config<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<Box<str>, QueueConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let queue_config = HashMap::<Box<str>, QueueConfig>::deserialize(deserializer)?;
+
+    match queue_config
+        .iter()
+        .find(|(name, config)| config.dead_letter.as_ref() == Some(*name))
+    {
+        Some((name, _)) => Err(D::Error::custom(format!(
+            "queue \"{}\" can't be its own dead letter queue",
+            name
+        ))),
+        None => Ok(queue_config),
+    }
+}
+
 /// Server configuration
 #[derive(Serialize, Deserialize)]
 pub struct Config<'a> {
@@ -50,12 +104,28 @@ pub struct Config<'a> {
     #[serde(skip_serializing)]
     pub gc_timer: u64,
 
+    /// Amount of seconds between timed out reservation checks
+    #[serde(default = "default_reclaim_timer")]
+    #[serde(skip_serializing)]
+    pub reclaim_timer: u64,
+
     /// Array of queues
+    #[serde(deserialize_with = "deserialize_queues")]
     pub queues: Box<[Box<str>]>,
 
     /// Persistence encryption key
     pub encryption_key: Option<Box<str>>,
 
+    /// Per-queue configuration
+    #[serde(default, deserialize_with = "deserialize_queue_config")]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub queue_config: HashMap<Box<str>, QueueConfig>,
+
+    /// Recurring message schedules
+    #[serde(default)]
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub schedules: Box<[ScheduleConfig]>,
+
     /// Queue access keys
     pub access_keys: Option<HashSet<Key>>,
 
@@ -73,8 +143,11 @@ impl Default for Config<'_> {
         Config {
             body_size: None,
             gc_timer: default_gc_timer(),
+            reclaim_timer: default_reclaim_timer(),
             queues: Box::new([]),
             encryption_key: None,
+            queue_config: HashMap::new(),
+            schedules: Box::new([]),
             access_keys: None,
             replication: None,
             persistence: Some(default_persistence()),
@@ -88,14 +161,43 @@ impl Default for Config<'_> {
         Config {
             body_size: None,
             gc_timer: 10,
+            reclaim_timer: default_reclaim_timer(),
             queues: Box::new([
                 String::from("test").into_boxed_str(),
                 String::from("test_2").into_boxed_str(),
             ]),
             encryption_key: None,
+            queue_config: HashMap::new(),
+            schedules: Box::new([]),
             access_keys: None,
             replication: None,
             persistence: Some(default_persistence()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use toml::from_str;
+
+    use super::Config;
+
+    #[test]
+    fn test_reserved_queue_name() {
+        assert!(from_str::<Config>("queues = [\"test\"]").is_ok());
+        assert!(from_str::<Config>("queues = [\"test\", \"metrics\"]").is_err());
+        assert!(from_str::<Config>("queues = [\"queues\"]").is_err());
+    }
+
+    #[test]
+    fn test_own_dead_letter() {
+        assert!(from_str::<Config>(
+            "queues = [\"test\", \"test_2\"]\n[queue_config.test]\ndead_letter = \"test_2\""
+        )
+        .is_ok());
+        assert!(from_str::<Config>(
+            "queues = [\"test\"]\n[queue_config.test]\ndead_letter = \"test\""
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+/// Max nack delay in seconds, that is used if queue has no configured one
+pub const DEFAULT_MAX_NACK_DELAY: u32 = 43_200;
+
+const fn default_backoff() -> Backoff {
+    Backoff::Fixed
+}
+
+/// Retry delay growth strategy
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum Backoff {
+    /// Each retry is delayed by the same amount of seconds
+    Fixed,
+
+    /// Retry delay grows linearly with each try
+    Linear,
+
+    /// Retry delay is doubled with each try
+    Exponential,
+}
+
+/// Queue retry policy
+///
+/// Applied to message on requeue, to avoid retrying failing messages right away
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RetryPolicy {
+    /// Retry delay growth strategy
+    #[serde(default = "default_backoff")]
+    pub backoff: Backoff,
+
+    /// Base amount of seconds to delay message for
+    pub delay: u32,
+
+    /// Max amount of seconds to delay message for, jitter excluded
+    pub max_delay: Option<u32>,
+
+    /// Max amount of random seconds, that are added to each delay
+    #[serde(default)]
+    pub jitter: u32,
+}
+
+impl RetryPolicy {
+    /// Calculate message delay in seconds, based on amount of already made tries
+    pub fn delay(&self, tries: u32) -> u32 {
+        let delay = match self.backoff {
+            Backoff::Fixed => self.delay,
+            Backoff::Linear => self.delay.saturating_mul(tries.max(1)),
+            Backoff::Exponential => self
+                .delay
+                .saturating_mul(2u32.saturating_pow(tries.saturating_sub(1))),
+        };
+
+        let delay = self
+            .max_delay
+            .map_or(delay, |max_delay| delay.min(max_delay));
+
+        if self.jitter > 0 {
+            delay.saturating_add(thread_rng().gen_range(0, self.jitter + 1))
+        } else {
+            delay
+        }
+    }
+}
+
+/// Single queue configuration
+#[derive(Serialize, Deserialize, Default)]
+pub struct QueueConfig {
+    /// Name of the queue, that receives messages with exhausted tries
+    ///
+    /// If not provided, exhausted messages are deleted by GC
+    pub dead_letter: Option<Box<str>>,
+
+    /// Retry policy, that is applied to requeued messages
+    ///
+    /// If not provided, requeued messages are available right away
+    pub retry: Option<RetryPolicy>,
+
+    /// Default TTL in seconds, that is applied to messages pushed without one
+    ///
+    /// If not provided, messages without TTL are kept until they are deleted or exhausted
+    pub ttl: Option<u32>,
+
+    /// Amount of seconds, during which repeated pushes with the same deduplication ID are discarded
+    ///
+    /// If not provided, 300 seconds window is used
+    pub deduplication_window: Option<u32>,
+
+    /// Max amount of seconds, that nacked message may be delayed for
+    ///
+    /// If not provided, 12 hours limit is used
+    pub max_nack_delay: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backoff, RetryPolicy};
+
+    fn policy(backoff: Backoff) -> RetryPolicy {
+        RetryPolicy {
+            backoff,
+            delay: 10,
+            max_delay: Some(100),
+            jitter: 0,
+        }
+    }
+
+    #[test]
+    fn test_fixed_delay() {
+        let policy = policy(Backoff::Fixed);
+        assert_eq!(policy.delay(1), 10);
+        assert_eq!(policy.delay(5), 10);
+    }
+
+    #[test]
+    fn test_linear_delay() {
+        let policy = policy(Backoff::Linear);
+        assert_eq!(policy.delay(1), 10);
+        assert_eq!(policy.delay(3), 30);
+        assert_eq!(policy.delay(50), 100);
+    }
+
+    #[test]
+    fn test_exponential_delay() {
+        let policy = policy(Backoff::Exponential);
+        assert_eq!(policy.delay(1), 10);
+        assert_eq!(policy.delay(2), 20);
+        assert_eq!(policy.delay(4), 80);
+        assert_eq!(policy.delay(5), 100);
+        assert_eq!(policy.delay(u32::MAX), 100);
+    }
+
+    #[test]
+    fn test_jitter() {
+        let policy = RetryPolicy {
+            jitter: 5,
+            ..policy(Backoff::Fixed)
+        };
+
+        for _ in 0..100 {
+            let delay = policy.delay(1);
+            assert!((10..=15).contains(&delay));
+        }
+    }
+}
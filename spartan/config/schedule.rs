@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+
+use crate::http::query::push::PushRequest;
+
+/// Recurring message schedule
+///
+/// Exactly one of `cron` and `interval` has to be provided.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduleConfig {
+    /// Schedule name, unique within queue
+    pub name: Box<str>,
+
+    /// Name of the queue, that receives scheduled messages
+    pub queue: Box<str>,
+
+    /// Cron expression with seconds field, evaluated in UTC
+    ///
+    /// Example: `0 */5 * * * *` pushes message every five minutes
+    pub cron: Option<Box<str>>,
+
+    /// Amount of seconds between pushes
+    pub interval: Option<u32>,
+
+    /// Pushed message
+    pub message: PushRequest,
+}
+
+impl ScheduleConfig {
+    /// Get time of the first push after provided time
+    ///
+    /// Returns [`None`] if schedule is invalid
+    pub fn next_run(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match (self.cron.as_deref(), self.interval) {
+            (Some(cron), None) => Schedule::from_str(cron).ok()?.after(&after).next(),
+            (None, Some(interval)) if interval > 0 => {
+                Some(after + Duration::seconds(interval.into()))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+
+    use super::ScheduleConfig;
+
+    fn schedule(cron: Option<&str>, interval: Option<u32>) -> ScheduleConfig {
+        ScheduleConfig {
+            name: "test".into(),
+            queue: "test".into(),
+            cron: cron.map(Into::into),
+            interval,
+            message: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_cron() {
+        let now = DateTime::parse_from_rfc3339("2021-01-01T10:02:30Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            schedule(Some("0 */5 * * * *"), None).next_run(now),
+            Some(
+                DateTime::parse_from_rfc3339("2021-01-01T10:05:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc)
+            )
+        );
+    }
+
+    #[test]
+    fn test_interval() {
+        let now = Utc::now();
+
+        assert_eq!(
+            schedule(None, Some(60)).next_run(now),
+            Some(now + Duration::seconds(60))
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let now = Utc::now();
+
+        assert!(schedule(None, None).next_run(now).is_none());
+        assert!(schedule(None, Some(0)).next_run(now).is_none());
+        assert!(schedule(Some("* * * * * *"), Some(60))
+            .next_run(now)
+            .is_none());
+        assert!(schedule(Some("every minute"), None).next_run(now).is_none());
+    }
+}
@@ -8,7 +8,11 @@ use warp::{
     Filter, Rejection,
 };
 
-use crate::{actions::RespondableError, config::Config, node::Manager};
+use crate::{
+    actions::RespondableError,
+    config::{key::WILDCARD_QUEUE, Config},
+    node::Manager,
+};
 
 #[derive(ThisError, Copy, Clone, Debug)]
 pub enum AccessError {
@@ -56,6 +60,25 @@ where
         .untuple_one()
 }
 
+/// Restrict access to node-level routes, that are not bound to single queue
+///
+/// If access keys are configured, then only keys with wildcard queue access are allowed.
+pub fn admin_access<T>(
+    filter: T,
+) -> impl Filter<Extract = T::Extract, Error = Rejection> + Clone + 'static
+where
+    T: Filter<Extract = (Arc<Manager<'static>>,), Error = Rejection> + Clone + 'static,
+{
+    filter.and(optional("Authorization")).and_then(
+        move |manager: Arc<Manager<'static>>, key: Option<String>| async move {
+            match AccessMiddleware::new(manager.config()).parse_request(key, WILDCARD_QUEUE) {
+                Ok(_) => Ok(manager),
+                Err(e) => Err(custom(e)),
+            }
+        },
+    )
+}
+
 impl AccessMiddleware {
     fn new(config: &'static Config<'static>) -> Self {
         AccessMiddleware { config }
@@ -193,4 +216,50 @@ mod tests {
 
         assert_eq!(resp.status(), StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn test_admin_access() {
+        let app = init_application!(&CONFIG);
+
+        let resp = request()
+            .path("/queues")
+            .header("Authorization", "Bearer testing")
+            .reply(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = request()
+            .path("/queues")
+            .header("Authorization", "Bearer wildcard")
+            .reply(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_access() {
+        let app = init_application!(&CONFIG);
+
+        let resp = request().path("/metrics").reply(&app).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp = request()
+            .path("/metrics")
+            .header("Authorization", "Bearer testing")
+            .reply(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = request()
+            .path("/metrics")
+            .header("Authorization", "Bearer wildcard")
+            .reply(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
 }
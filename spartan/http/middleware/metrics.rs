@@ -0,0 +1,31 @@
+use std::{sync::Arc, time::Instant};
+
+use warp::{any, reply::Response, Filter, Rejection, Reply};
+
+use crate::node::Manager;
+
+/// Record latency and response status of action
+///
+/// Requests, that were rejected before reaching action, are not recorded.
+pub fn instrument<T, R>(
+    filter: T,
+    manager: Arc<Manager<'static>>,
+    action: &'static str,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone + 'static
+where
+    T: Filter<Extract = (R,), Error = Rejection> + Clone + 'static,
+    R: Reply + 'static,
+{
+    any()
+        .map(Instant::now)
+        .and(filter)
+        .map(move |started: Instant, reply: R| {
+            let response = reply.into_response();
+
+            manager
+                .metrics()
+                .observe_action(action, started.elapsed(), response.status().as_u16());
+
+            response
+        })
+}
@@ -1,2 +1,5 @@
 /// Queue access middleware
 pub mod access;
+
+/// Action metrics middleware
+pub mod metrics;
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use spartan_lib::{core::message::Message, uuid::Uuid};
+
+use crate::http::query::pop::PopResponse;
+
+/// Max amount of messages in single popped batch
+const MAX_COUNT: u32 = 1000;
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct PopBatchQuery {
+    pub count: u32,
+}
+
+impl PopBatchQuery {
+    pub fn count(&self) -> u32 {
+        self.count.min(MAX_COUNT)
+    }
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct BatchRequest {
+    pub ids: Vec<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct PopBatchResponse<'m> {
+    messages: Vec<PopResponse<'m>>,
+}
+
+impl<'m> From<Vec<&'m Message>> for PopBatchResponse<'m> {
+    fn from(messages: Vec<&'m Message>) -> Self {
+        PopBatchResponse {
+            messages: messages.into_iter().map(PopResponse::from).collect(),
+        }
+    }
+}
+
+/// IDs of messages, that were processed by batch operation
+///
+/// Messages, that were not found or could not be processed, are omitted
+#[derive(Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct BatchResponse {
+    pub ids: Vec<Uuid>,
+}
+
+impl From<Vec<Uuid>> for BatchResponse {
+    fn from(ids: Vec<Uuid>) -> Self {
+        BatchResponse { ids }
+    }
+}
+
+#[cfg(test)]
+pub mod test_response {
+    use serde::Deserialize;
+
+    use crate::http::query::pop::test_response::TestPopResponse;
+
+    #[derive(Deserialize)]
+    pub struct TestPopBatchResponse {
+        pub messages: Vec<TestPopResponse>,
+    }
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Default, serde::Serialize))]
+pub struct ExtendRequest {
+    pub id: Uuid,
+    pub timeout: Option<u32>,
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+use spartan_lib::core::message::{Attempt as MessageAttempt, History, Outcome};
+
+#[derive(Serialize)]
+pub struct Attempt<'m> {
+    obtained_at: &'m DateTime<FixedOffset>,
+    outcome: Option<Outcome>,
+    finished_at: &'m Option<DateTime<FixedOffset>>,
+}
+
+impl<'m> From<&'m MessageAttempt> for Attempt<'m> {
+    fn from(attempt: &'m MessageAttempt) -> Self {
+        Attempt {
+            obtained_at: attempt.obtained_at(),
+            outcome: attempt.outcome(),
+            finished_at: attempt.finished_at(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct HistoryResponse<'m> {
+    history: Vec<Attempt<'m>>,
+}
+
+impl<'m> From<&'m History> for HistoryResponse<'m> {
+    fn from(history: &'m History) -> Self {
+        HistoryResponse {
+            history: history.attempts().iter().map(Attempt::from).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_response {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    pub struct TestAttempt {
+        pub obtained_at: DateTime<FixedOffset>,
+        pub outcome: Option<Outcome>,
+        pub finished_at: Option<DateTime<FixedOffset>>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct TestHistoryResponse {
+        pub history: Vec<TestAttempt>,
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use spartan_lib::core::{
+    message::Message,
+    payload::{Dispatchable, Status},
+};
+
+use crate::http::query::pop::PopResponse;
+
+/// Default amount of messages in single page
+const DEFAULT_LIMIT: u32 = 100;
+
+/// Max amount of messages in single page
+const MAX_LIMIT: u32 = 1000;
+
+#[derive(Deserialize, Clone, Copy)]
+#[cfg_attr(test, derive(Serialize))]
+#[serde(rename_all = "lowercase")]
+pub enum MessageStatus {
+    /// Message can be reserved right now
+    Available,
+    /// Message is reserved
+    Transit,
+    /// Message is waiting for its delay
+    Delayed,
+    /// Message has no tries left, or its TTL has expired
+    Exhausted,
+}
+
+impl MessageStatus {
+    pub fn matches(self, message: &Message) -> bool {
+        match self {
+            MessageStatus::Available => message.reservable() && message.obtainable(),
+            MessageStatus::Transit => message.requeueable(),
+            MessageStatus::Delayed => {
+                message.reservable() && !message.expired() && !message.obtainable()
+            }
+            MessageStatus::Exhausted => {
+                !message.requeueable() && (!message.has_tries() || message.expired())
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Default, Serialize))]
+pub struct MessagesQuery {
+    pub status: Option<MessageStatus>,
+    pub limit: Option<u32>,
+    pub cursor: Option<u64>,
+}
+
+impl MessagesQuery {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize
+    }
+}
+
+#[derive(Serialize)]
+pub struct MessagesResponse<'m> {
+    messages: Vec<PopResponse<'m>>,
+    next_cursor: Option<u64>,
+}
+
+impl<'m> MessagesResponse<'m> {
+    pub fn new(messages: Vec<&'m Message>, next_cursor: Option<u64>) -> Self {
+        MessagesResponse {
+            messages: messages.into_iter().map(PopResponse::from).collect(),
+            next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_response {
+    use serde::Deserialize;
+
+    use crate::http::query::pop::test_response::TestPopResponse;
+
+    #[derive(Deserialize)]
+    pub struct TestMessagesResponse {
+        pub messages: Vec<TestPopResponse>,
+        pub next_cursor: Option<u64>,
+    }
+}
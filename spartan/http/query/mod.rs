@@ -1,5 +1,15 @@
+pub mod batch;
 pub mod delete;
+pub mod extend;
+pub mod history;
+pub mod messages;
+pub mod nack;
 pub mod pop;
+pub mod purge;
 pub mod push;
+pub mod queues;
 pub mod requeue;
+pub mod schedule;
 pub mod size;
+pub mod stats;
+pub mod update;
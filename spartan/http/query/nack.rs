@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Default, serde::Serialize))]
+pub struct NackRequest {
+    pub id: Uuid,
+    pub reason: Option<Box<str>>,
+    pub delay: Option<u32>,
+}
@@ -1,10 +1,15 @@
+use std::borrow::Cow;
+
+use base64::encode;
 use chrono::{DateTime, FixedOffset};
 use serde::Serialize;
 use spartan_lib::core::{
-    message::{Message, State},
-    payload::{Dispatchable, Identifiable},
+    message::{attributes::Attributes, Message, State},
+    payload::{Dispatchable, Groupable, Identifiable},
 };
 
+use crate::http::query::history::Attempt;
+
 #[derive(Serialize)]
 pub struct Timeout<'m> {
     max: &'m u32,
@@ -13,33 +18,68 @@ pub struct Timeout<'m> {
 
 #[derive(Serialize)]
 pub struct Time<'m> {
+    timezone: Option<&'static str>,
     dispatched_at: &'m DateTime<FixedOffset>,
     delay: &'m Option<DateTime<FixedOffset>>,
     timeout: Timeout<'m>,
+    expires_at: &'m Option<DateTime<FixedOffset>>,
+}
+
+/// Encoding of message body in JSON responses
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// Body is valid UTF-8, and is returned as-is
+    Utf8,
+
+    /// Body contains binary data, and is returned as base64 string
+    Base64,
 }
 
 #[derive(Serialize)]
 pub struct PopResponse<'m> {
     id: <Message as Identifiable>::Id,
-    body: &'m <Message as Dispatchable>::Body,
+    body: Cow<'m, str>,
+    encoding: Encoding,
     state: &'m State,
     time: Time<'m>,
+    history: Vec<Attempt<'m>>,
+    attributes: &'m Attributes,
+    priority: &'m u8,
+    group_id: Option<&'m str>,
 }
 
 impl<'m> From<&'m Message> for PopResponse<'m> {
     fn from(message: &'m Message) -> Self {
+        let (body, encoding) = match message.body().as_str() {
+            Some(body) => (Cow::Borrowed(body), Encoding::Utf8),
+            None => (Cow::Owned(encode(message.body())), Encoding::Base64),
+        };
+
         PopResponse {
             id: message.id(),
-            body: message.body(),
+            body,
+            encoding,
             state: message.state(),
             time: Time {
+                timezone: message.time().timezone().map(|timezone| timezone.name()),
                 dispatched_at: message.time().dispatched_at(),
                 delay: message.time().delay(),
                 timeout: Timeout {
                     max: message.time().timeout().max(),
                     obtained_at: message.time().timeout().obtained_at(),
                 },
+                expires_at: message.time().expires_at(),
             },
+            history: message
+                .history()
+                .attempts()
+                .iter()
+                .map(Attempt::from)
+                .collect(),
+            attributes: message.attributes(),
+            priority: message.priority(),
+            group_id: message.group().map(|group_id| &**group_id),
         }
     }
 }
@@ -54,5 +94,6 @@ pub mod test_response {
     pub struct TestPopResponse {
         pub id: <Message as Identifiable>::Id,
         pub body: Box<<Message as Dispatchable>::Body>,
+        pub attributes: Attributes,
     }
 }
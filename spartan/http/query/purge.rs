@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use spartan_lib::core::{message::Message, payload::Dispatchable};
+
+use crate::http::query::messages::MessageStatus;
+
+/// Filter of purged messages
+///
+/// Message is purged only if it matches all provided conditions
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Default, Serialize))]
+pub struct PurgeRequest {
+    pub status: Option<MessageStatus>,
+    pub min_tries: Option<u32>,
+    pub older_than: Option<u32>,
+    pub body_contains: Option<Box<str>>,
+    pub attributes: Option<BTreeMap<Box<str>, Box<str>>>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl PurgeRequest {
+    /// Check if filter has no conditions, so it matches every message
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.min_tries.is_none()
+            && self.older_than.is_none()
+            && self
+                .body_contains
+                .as_ref()
+                .filter(|needle| !needle.is_empty())
+                .is_none()
+            && self
+                .attributes
+                .as_ref()
+                .filter(|attributes| !attributes.is_empty())
+                .is_none()
+    }
+
+    pub fn matches(&self, message: &Message, now: DateTime<Utc>) -> bool {
+        if let Some(status) = self.status {
+            if !status.matches(message) {
+                return false;
+            }
+        }
+
+        if let Some(tries) = self.min_tries {
+            if *message.state().tries() < tries {
+                return false;
+            }
+        }
+
+        if let Some(age) = self.older_than {
+            let dispatched_at = *message.time().dispatched_at();
+
+            if now.signed_duration_since(dispatched_at) < Duration::seconds(i64::from(age)) {
+                return false;
+            }
+        }
+
+        if let Some(needle) = &self.body_contains {
+            let body = message.body();
+
+            if !needle.is_empty()
+                && !body
+                    .windows(needle.len())
+                    .any(|window| window == needle.as_bytes())
+            {
+                return false;
+            }
+        }
+
+        if let Some(attributes) = &self.attributes {
+            return attributes
+                .iter()
+                .all(|(key, value)| message.attributes().get(key) == Some(&**value));
+        }
+
+        true
+    }
+}
+
+#[derive(Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct PurgeResponse {
+    pub count: usize,
+}
+
+impl From<usize> for PurgeResponse {
+    fn from(count: usize) -> Self {
+        PurgeResponse { count }
+    }
+}
@@ -1,41 +1,209 @@
-use std::convert::TryFrom;
+use std::{collections::BTreeMap, convert::TryFrom};
 
-use serde::Deserialize;
-use spartan_lib::core::message::{
-    builder::{BuilderError, MessageBuilder},
-    Message,
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use spartan_lib::{
+    core::{
+        message::{
+            builder::{BuilderError, MessageBuilder},
+            Message,
+        },
+        payload::Identifiable,
+    },
+    uuid::Uuid,
 };
 
-#[derive(Deserialize)]
-#[cfg_attr(test, derive(Default, serde::Serialize))]
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(test, derive(Default))]
 pub struct PushRequest {
+    pub id: Option<Uuid>,
     pub body: Box<str>,
     pub offset: Option<i32>,
+    pub timezone: Option<Box<str>>,
+    pub max_tries: Option<u32>,
+    pub timeout: Option<u32>,
+    pub delay: Option<u32>,
+    pub deliver_at: Option<DeliveryTime>,
+    #[serde(default)]
+    pub allow_past_delivery: bool,
+    pub priority: Option<u8>,
+    pub ttl: Option<u32>,
+    pub attributes: Option<BTreeMap<Box<str>, Box<str>>>,
+    pub deduplication_id: Option<Box<str>>,
+    pub group_id: Option<Box<str>>,
+}
+
+/// Message delivery time
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum DeliveryTime {
+    /// RFC 3339 timestamp with offset
+    Absolute(DateTime<FixedOffset>),
+
+    /// Local wall-clock time without offset, that is resolved in message timezone
+    Local(NaiveDateTime),
+}
+
+/// Query parameters of raw `application/octet-stream` push
+///
+/// Attributes are not supported, as query string has no representation for nested maps.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Default, serde::Serialize))]
+pub struct RawPushQuery {
+    pub id: Option<Uuid>,
+    pub offset: Option<i32>,
+    pub timezone: Option<Box<str>>,
     pub max_tries: Option<u32>,
     pub timeout: Option<u32>,
     pub delay: Option<u32>,
+    pub deliver_at: Option<DeliveryTime>,
+    #[serde(default)]
+    pub allow_past_delivery: bool,
+    pub priority: Option<u8>,
+    pub ttl: Option<u32>,
+    pub deduplication_id: Option<Box<str>>,
+    pub group_id: Option<Box<str>>,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+pub struct PushResponse {
+    pub id: <Message as Identifiable>::Id,
+}
+
+fn apply_options(
+    mut builder: MessageBuilder,
+    offset: Option<i32>,
+    max_tries: Option<u32>,
+    timeout: Option<u32>,
+    priority: Option<u8>,
+    ttl: Option<u32>,
+) -> MessageBuilder {
+    if let Some(offset) = offset {
+        builder = builder.offset(offset);
+    };
+
+    if let Some(max_tries) = max_tries {
+        builder = builder.max_tries(max_tries);
+    };
+
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    };
+
+    if let Some(priority) = priority {
+        builder = builder.priority(priority);
+    };
+
+    if let Some(ttl) = ttl {
+        builder = builder.ttl(ttl);
+    };
+
+    builder
+}
+
+/// Delivery time offset is used as message offset, unless offset is provided explicitly
+fn delivery_offset(offset: Option<i32>, deliver_at: &Option<DeliveryTime>) -> Option<i32> {
+    offset.or_else(|| match deliver_at {
+        Some(DeliveryTime::Absolute(deliver_at)) => Some(deliver_at.offset().local_minus_utc()),
+        _ => None,
+    })
+}
+
+fn apply_delivery(
+    mut builder: MessageBuilder,
+    delay: Option<u32>,
+    deliver_at: Option<DeliveryTime>,
+    allow_past_delivery: bool,
+) -> Result<MessageBuilder, BuilderError> {
+    match (delay, deliver_at) {
+        (Some(_), Some(_)) => return Err(BuilderError::DelayWithDeliveryTime),
+        (Some(delay), None) => builder = builder.delay(delay),
+        (None, Some(deliver_at)) => {
+            builder = match deliver_at {
+                DeliveryTime::Absolute(deliver_at) => builder.deliver_at(deliver_at),
+                DeliveryTime::Local(deliver_at) => builder.deliver_at_local(deliver_at),
+            }
+            .allow_past_delivery(allow_past_delivery);
+        }
+        (None, None) => (),
+    };
+
+    Ok(builder)
 }
 
 impl TryFrom<PushRequest> for Message {
     type Error = BuilderError;
 
     fn try_from(request: PushRequest) -> Result<Message, Self::Error> {
-        let mut builder = MessageBuilder::default().body(request.body);
+        let builder = apply_options(
+            MessageBuilder::default().body(request.body),
+            delivery_offset(request.offset, &request.deliver_at),
+            request.max_tries,
+            request.timeout,
+            request.priority,
+            request.ttl,
+        );
+
+        let mut builder = apply_delivery(
+            builder,
+            request.delay,
+            request.deliver_at,
+            request.allow_past_delivery,
+        )?;
+
+        if let Some(timezone) = request.timezone {
+            builder = builder.timezone(timezone);
+        };
 
-        if let Some(offset) = request.offset {
-            builder = builder.offset(offset);
+        if let Some(id) = request.id {
+            builder = builder.id(id);
         };
 
-        if let Some(max_tries) = request.max_tries {
-            builder = builder.max_tries(max_tries);
+        if let Some(group_id) = request.group_id {
+            builder = builder.group_id(group_id);
+        };
+
+        if let Some(attributes) = request.attributes {
+            for (key, value) in attributes {
+                builder = builder.attribute(key, value);
+            }
+        };
+
+        builder.compose()
+    }
+}
+
+impl RawPushQuery {
+    /// Compose message with provided raw body
+    pub fn compose(self, body: Bytes) -> Result<Message, BuilderError> {
+        let builder = apply_options(
+            MessageBuilder::default().body(&*body),
+            delivery_offset(self.offset, &self.deliver_at),
+            self.max_tries,
+            self.timeout,
+            self.priority,
+            self.ttl,
+        );
+
+        let mut builder = apply_delivery(
+            builder,
+            self.delay,
+            self.deliver_at,
+            self.allow_past_delivery,
+        )?;
+
+        if let Some(timezone) = self.timezone {
+            builder = builder.timezone(timezone);
         };
 
-        if let Some(timeout) = request.timeout {
-            builder = builder.timeout(timeout);
+        if let Some(id) = self.id {
+            builder = builder.id(id);
         };
 
-        if let Some(delay) = request.delay {
-            builder = builder.delay(delay);
+        if let Some(group_id) = self.group_id {
+            builder = builder.group_id(group_id);
         };
 
         builder.compose()
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct CreateQueueRequest {
+    pub name: Box<str>,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+pub struct QueueEntry {
+    pub name: Box<str>,
+    /// Queue was created at runtime, and may be deleted
+    pub runtime: bool,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+pub struct QueuesResponse {
+    pub queues: Vec<QueueEntry>,
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::schedule::ScheduleConfig, http::query::push::PushRequest, node::schedule::Schedule,
+};
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct ScheduleRequest {
+    pub name: Box<str>,
+    pub cron: Option<Box<str>>,
+    pub interval: Option<u32>,
+    pub message: PushRequest,
+}
+
+impl ScheduleRequest {
+    /// Compose schedule config of provided queue
+    pub fn into_config(self, queue: String) -> ScheduleConfig {
+        ScheduleConfig {
+            name: self.name,
+            queue: queue.into_boxed_str(),
+            cron: self.cron,
+            interval: self.interval,
+            message: self.message,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub struct UnscheduleRequest {
+    pub name: Box<str>,
+}
+
+#[derive(Serialize)]
+pub struct ScheduleResponse<'s> {
+    name: &'s str,
+    cron: Option<&'s str>,
+    interval: &'s Option<u32>,
+    next_run: &'s DateTime<Utc>,
+    message: &'s PushRequest,
+}
+
+impl<'s> From<&'s Schedule> for ScheduleResponse<'s> {
+    fn from(schedule: &'s Schedule) -> Self {
+        ScheduleResponse {
+            name: &schedule.config.name,
+            cron: schedule.config.cron.as_deref(),
+            interval: &schedule.config.interval,
+            next_run: &schedule.next_run,
+            message: &schedule.config.message,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct SchedulesResponse<'s> {
+    schedules: Vec<ScheduleResponse<'s>>,
+}
+
+impl<'s, I> From<I> for SchedulesResponse<'s>
+where
+    I: Iterator<Item = &'s Schedule>,
+{
+    fn from(schedules: I) -> Self {
+        SchedulesResponse {
+            schedules: schedules.map(ScheduleResponse::from).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_response {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    pub struct TestScheduleResponse {
+        pub name: Box<str>,
+        pub next_run: DateTime<Utc>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct TestSchedulesResponse {
+        pub schedules: Vec<TestScheduleResponse>,
+    }
+}
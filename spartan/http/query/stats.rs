@@ -0,0 +1,62 @@
+use serde::Serialize;
+use spartan_lib::core::db::StatusCounts;
+
+use crate::node::counters::{Counter, Counters};
+
+/// Amount of messages in each state
+///
+/// Each message is counted only once, with transit, expired and exhausted states checked first
+#[derive(Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+pub struct Messages {
+    pub available: usize,
+    pub transit: usize,
+    pub delayed: usize,
+    pub exhausted: usize,
+    pub expired: usize,
+}
+
+impl From<&StatusCounts> for Messages {
+    fn from(counts: &StatusCounts) -> Self {
+        Messages {
+            available: counts.available,
+            transit: counts.transit,
+            delayed: counts.delayed,
+            exhausted: counts.exhausted,
+            expired: counts.expired,
+        }
+    }
+}
+
+/// Totals of queue operations since start
+#[derive(Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+pub struct Totals {
+    pub pushes: u64,
+    pub pops: u64,
+    pub requeues: u64,
+    pub deletes: u64,
+    pub gc_removals: u64,
+}
+
+impl From<&Counters> for Totals {
+    fn from(counters: &Counters) -> Self {
+        Totals {
+            pushes: counters.get(Counter::Push),
+            pops: counters.get(Counter::Pop),
+            requeues: counters.get(Counter::Requeue),
+            deletes: counters.get(Counter::Delete),
+            gc_removals: counters.get(Counter::Gc),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+pub struct StatsResponse {
+    pub size: usize,
+    pub messages: Messages,
+    /// Age of the oldest message in seconds, [`None`] if queue is empty
+    pub oldest_message_age: Option<i64>,
+    pub totals: Totals,
+}
@@ -0,0 +1,23 @@
+use serde::Deserialize;
+use spartan_lib::core::message::Patch;
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Default, serde::Serialize))]
+pub struct UpdateRequest {
+    pub body: Option<Box<str>>,
+    pub delay: Option<u32>,
+    pub max_tries: Option<u32>,
+    pub timeout: Option<u32>,
+}
+
+impl UpdateRequest {
+    /// Convert request into patch, resolving delay against provided UTC timestamp
+    pub fn into_patch(self, now: i64) -> Patch {
+        Patch {
+            body: self.body.map(Into::into),
+            available_at: self.delay.map(|delay| now + i64::from(delay)),
+            max_tries: self.max_tries,
+            timeout: self.timeout,
+        }
+    }
+}
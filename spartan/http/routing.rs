@@ -1,10 +1,20 @@
 use std::{convert::Infallible, sync::Arc};
 
-use warp::{any, body::json, delete, get, path, post, wrap_fn, Filter, Rejection, Reply};
+use spartan_lib::uuid::Uuid;
+use warp::{
+    any,
+    body::{bytes, json},
+    delete, get,
+    header::exact,
+    patch, path, post, query, wrap_fn, Filter, Rejection, Reply,
+};
 
 use crate::{
     actions::ResponseError,
-    http::middleware::access::{access, AccessError},
+    http::middleware::{
+        access::{access, admin_access, AccessError},
+        metrics::instrument,
+    },
     node::Manager,
 };
 
@@ -14,6 +24,14 @@ macro_rules! route {
     };
 }
 
+/// Record action metrics of route
+macro_rules! instrument {
+    ($manager:ident, $name:ident) => {{
+        let manager = $manager.clone();
+        wrap_fn(move |filter| instrument(filter, manager.clone(), stringify!($name)))
+    }};
+}
+
 fn with_manager(
     manager: Arc<Manager<'static>>,
 ) -> impl Filter<Extract = (Arc<Manager<'static>>,), Error = Infallible> + Clone {
@@ -24,47 +42,244 @@ fn with_manager(
 pub fn attach_routes(
     manager: Arc<Manager<'static>>,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let queues = with_manager(manager.clone())
+        .and(get())
+        .and(path!("queues"))
+        .with(wrap_fn(admin_access))
+        .map_async(route!(queues))
+        .with(instrument!(manager, queues));
+
+    let create_queue = with_manager(manager.clone())
+        .and(post())
+        .and(path!("queues"))
+        .with(wrap_fn(admin_access))
+        .and(json())
+        .map_async(route!(create_queue))
+        .with(instrument!(manager, create_queue));
+
+    let delete_queue = with_manager(manager.clone())
+        .and(delete())
+        .and(path::path("queues"))
+        .with(wrap_fn(admin_access))
+        .and(path!(String))
+        .map_async(route!(delete_queue))
+        .with(instrument!(manager, delete_queue));
+
     let pop = with_manager(manager.clone())
         .and(get())
         .and(path!(String))
         .with(wrap_fn(access))
-        .map_async(route!(pop));
+        .map_async(route!(pop))
+        .with(instrument!(manager, pop));
 
     let push = with_manager(manager.clone())
         .and(post())
         .and(path!(String))
         .with(wrap_fn(access))
         .and(json())
-        .map_async(route!(push));
+        .map_async(route!(push))
+        .with(instrument!(manager, push));
+
+    let pop_raw = with_manager(manager.clone())
+        .and(get())
+        .and(exact("accept", "application/octet-stream"))
+        .and(path!(String))
+        .with(wrap_fn(access))
+        .map_async(route!(pop_raw))
+        .with(instrument!(manager, pop_raw));
+
+    let push_raw = with_manager(manager.clone())
+        .and(post())
+        .and(exact("content-type", "application/octet-stream"))
+        .and(path!(String))
+        .with(wrap_fn(access))
+        .and(query())
+        .and(bytes())
+        .map_async(route!(push_raw))
+        .with(instrument!(manager, push_raw));
+
+    let schedule = with_manager(manager.clone())
+        .and(post())
+        .and(path!(String / "schedules"))
+        .with(wrap_fn(access))
+        .and(json())
+        .map_async(route!(schedule))
+        .with(instrument!(manager, schedule));
+
+    let schedules = with_manager(manager.clone())
+        .and(get())
+        .and(path!(String / "schedules"))
+        .with(wrap_fn(access))
+        .map_async(route!(schedules))
+        .with(instrument!(manager, schedules));
+
+    let unschedule = with_manager(manager.clone())
+        .and(delete())
+        .and(path!(String / "schedules"))
+        .with(wrap_fn(access))
+        .and(json())
+        .map_async(route!(unschedule))
+        .with(instrument!(manager, unschedule));
+
+    let delete_batch = with_manager(manager.clone())
+        .and(delete())
+        .and(path!(String / "batch"))
+        .with(wrap_fn(access))
+        .and(json())
+        .map_async(route!(delete_batch))
+        .with(instrument!(manager, delete_batch));
 
     let delete = with_manager(manager.clone())
         .and(delete())
         .and(path!(String))
         .with(wrap_fn(access))
         .and(json())
-        .map_async(route!(delete));
+        .map_async(route!(delete))
+        .with(instrument!(manager, delete));
+
+    let push_batch = with_manager(manager.clone())
+        .and(post())
+        .and(path!(String / "batch"))
+        .with(wrap_fn(access))
+        .and(json())
+        .map_async(route!(push_batch))
+        .with(instrument!(manager, push_batch));
+
+    let pop_batch = with_manager(manager.clone())
+        .and(get())
+        .and(path!(String / "batch"))
+        .with(wrap_fn(access))
+        .and(query())
+        .map_async(route!(pop_batch))
+        .with(instrument!(manager, pop_batch));
+
+    let requeue_batch = with_manager(manager.clone())
+        .and(post())
+        .and(path!(String / "requeue" / "batch"))
+        .with(wrap_fn(access))
+        .and(json())
+        .map_async(route!(requeue_batch))
+        .with(instrument!(manager, requeue_batch));
 
     let requeue = with_manager(manager.clone())
         .and(post())
         .and(path!(String / "requeue"))
         .with(wrap_fn(access))
         .and(json())
-        .map_async(route!(requeue));
+        .map_async(route!(requeue))
+        .with(instrument!(manager, requeue));
+
+    let history = with_manager(manager.clone())
+        .and(get())
+        .and(path::param())
+        .and(path::path("messages"))
+        .with(wrap_fn(access))
+        .and(path!(Uuid / "history"))
+        .map_async(route!(history))
+        .with(instrument!(manager, history));
+
+    let message = with_manager(manager.clone())
+        .and(get())
+        .and(path::param())
+        .and(path::path("messages"))
+        .with(wrap_fn(access))
+        .and(path!(Uuid))
+        .map_async(route!(message))
+        .with(instrument!(manager, message));
+
+    let update = with_manager(manager.clone())
+        .and(patch())
+        .and(path::param())
+        .and(path::path("messages"))
+        .with(wrap_fn(access))
+        .and(path!(Uuid))
+        .and(json())
+        .map_async(route!(update))
+        .with(instrument!(manager, update));
+
+    let messages = with_manager(manager.clone())
+        .and(get())
+        .and(path!(String / "messages"))
+        .with(wrap_fn(access))
+        .and(query())
+        .map_async(route!(messages))
+        .with(instrument!(manager, messages));
+
+    let nack = with_manager(manager.clone())
+        .and(post())
+        .and(path!(String / "nack"))
+        .with(wrap_fn(access))
+        .and(json())
+        .map_async(route!(nack))
+        .with(instrument!(manager, nack));
+
+    let extend = with_manager(manager.clone())
+        .and(post())
+        .and(path!(String / "extend"))
+        .with(wrap_fn(access))
+        .and(json())
+        .map_async(route!(extend))
+        .with(instrument!(manager, extend));
+
+    let purge = with_manager(manager.clone())
+        .and(post())
+        .and(path!(String / "purge"))
+        .with(wrap_fn(access))
+        .and(json())
+        .map_async(route!(purge))
+        .with(instrument!(manager, purge));
 
     let clear = with_manager(manager.clone())
         .and(post())
         .and(path!(String / "clear"))
         .with(wrap_fn(access))
-        .map_async(route!(clear));
+        .map_async(route!(clear))
+        .with(instrument!(manager, clear));
 
-    let size = with_manager(manager)
+    let stats = with_manager(manager.clone())
+        .and(get())
+        .and(path!(String / "stats"))
+        .with(wrap_fn(access))
+        .map_async(route!(stats))
+        .with(instrument!(manager, stats));
+
+    let size = with_manager(manager.clone())
         .and(get())
         .and(path!(String / "size"))
         .with(wrap_fn(access))
-        .map_async(route!(size));
+        .map_async(route!(size))
+        .with(instrument!(manager, size));
+
+    let metrics = with_manager(manager)
+        .and(get())
+        .and(path!("metrics"))
+        .with(wrap_fn(admin_access))
+        .map_async(route!(metrics));
 
-    size.or(clear)
+    metrics
+        .or(queues)
+        .or(create_queue)
+        .or(delete_queue)
+        .or(size)
+        .or(stats)
+        .or(clear)
+        .or(purge)
         .or(requeue)
+        .or(requeue_batch)
+        .or(pop_batch)
+        .or(push_batch)
+        .or(delete_batch)
+        .or(nack)
+        .or(history)
+        .or(messages)
+        .or(message)
+        .or(update)
+        .or(extend)
+        .or(schedule)
+        .or(schedules)
+        .or(unschedule)
+        .or(pop_raw)
+        .or(push_raw)
         .or(pop)
         .or(push)
         .or(delete)
@@ -1,32 +1,57 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures_util::{
     stream::{iter, StreamExt},
     TryStreamExt,
 };
-use spartan_lib::core::dispatcher::SimpleDispatcher;
+use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
 use tokio::time::delay_for;
 
 #[cfg(feature = "replication")]
 use crate::node::replication::primary::storage::PrimaryStorage;
-use crate::node::{event::Event, persistence::PersistenceError, Manager};
+use crate::node::{counters::Counter, event::Event, persistence::PersistenceError, Manager};
 
 /// Concurrently iterates over all databases in node, and executes GC on them.
+///
+/// Messages with timed out reservation are returned to queue first.
+/// Exhausted messages are moved to dead letter queue, if there is any.
+/// Expired deduplication index entries are removed.
 async fn execute_gc(manager: &Manager<'_>) -> Result<(), PersistenceError> {
     iter(manager.node().iter())
         .map(Ok)
         .try_for_each_concurrent(None, |(name, queue)| async move {
             info!("Started GC cycle on database \"{}\"", name);
 
-            queue.log_event(name, manager, Event::Gc).await?;
+            let started = Instant::now();
+
+            // Timed out messages are returned to queue instead of being collected
+            manager.reclaim_expired(&name, &queue).await?;
+
+            if manager.dead_letter_queue(&name).is_some() {
+                let exhausted = queue.database().await.exhausted_ids();
+                queue.counters().add(Counter::Gc, exhausted.len());
+                manager.dead_letter(&name, &queue, exhausted).await?;
+            }
+
+            queue.log_event(&name, manager, Event::Gc).await?;
 
-            queue.database().await.gc();
+            {
+                let mut database = queue.database().await;
+                let size = database.size();
+
+                database.gc();
+                queue.counters().add(Counter::Gc, size - database.size());
+            }
+
+            queue.deduplication().await.prune();
 
             #[cfg(feature = "replication")]
             if let Some(storage) = queue.replication_storage().await.as_mut() {
                 storage.map_primary(PrimaryStorage::gc);
             }
 
+            manager.metrics().observe_gc(&name, started.elapsed());
+
             info!("GC cycle on \"{}\" completed successfully", name);
 
             Ok(())
@@ -53,12 +78,36 @@ pub async fn spawn_gc(manager: &Manager<'_>) {
 
 #[cfg(test)]
 mod tests {
+    use chrono::Utc;
     use spartan_lib::core::{
-        dispatcher::SimpleDispatcher, message::builder::MessageBuilder, payload::Status,
+        db::Database,
+        dispatcher::{PositionBasedDelete, SimpleDispatcher},
+        message::{builder::MessageBuilder, Outcome},
+        payload::Status,
     };
 
+    use once_cell::sync::Lazy;
+    use spartan_lib::core::{dispatcher::StatusAwareDispatcher, payload::Identifiable};
+
     use super::execute_gc;
-    use crate::{node::Manager, utils::testing::CONFIG};
+    use crate::{
+        config::{queue::QueueConfig, Config},
+        node::{counters::Counter, Manager},
+        utils::testing::CONFIG,
+    };
+
+    static DEAD_LETTER_CONFIG: Lazy<Config> = Lazy::new(|| Config {
+        queue_config: vec![(
+            String::from("test").into_boxed_str(),
+            QueueConfig {
+                dead_letter: Some(String::from("test_2").into_boxed_str()),
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    });
 
     #[tokio::test]
     async fn test_gc() {
@@ -72,8 +121,8 @@ mod tests {
             .compose()
             .unwrap();
 
-        message.reserve();
-        message.requeue();
+        message.reserve(Utc::now().timestamp());
+        message.requeue(Utc::now().timestamp());
         manager
             .queue("first")
             .unwrap()
@@ -86,5 +135,125 @@ mod tests {
         execute_gc(&manager).await.unwrap();
 
         assert_eq!(manager.queue("first").unwrap().database().await.size(), 0);
+        assert_eq!(
+            manager.queue("first").unwrap().counters().get(Counter::Gc),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gc_dead_letter() {
+        let manager = Manager::new(&DEAD_LETTER_CONFIG);
+
+        let mut message = MessageBuilder::default()
+            .body("Hello, world")
+            .max_tries(1)
+            .compose()
+            .unwrap();
+
+        message.reserve(Utc::now().timestamp());
+        message.requeue(Utc::now().timestamp());
+        manager
+            .queue("test")
+            .unwrap()
+            .database()
+            .await
+            .push(message.clone());
+
+        execute_gc(&manager).await.unwrap();
+
+        assert_eq!(manager.queue("test").unwrap().database().await.size(), 0);
+        assert_eq!(
+            manager.queue("test").unwrap().counters().get(Counter::Gc),
+            1
+        );
+        assert_eq!(
+            manager
+                .queue("test_2")
+                .unwrap()
+                .counters()
+                .get(Counter::Push),
+            1
+        );
+
+        let dead_letter = manager.queue("test_2").unwrap();
+        let mut dead_letter = dead_letter.database().await;
+        let dead_message = dead_letter.pop(Utc::now().timestamp()).unwrap();
+
+        assert_eq!(dead_message.id(), message.id());
+        assert_eq!(*dead_message.state().tries(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_gc_dead_letter_conflict() {
+        let manager = Manager::new(&DEAD_LETTER_CONFIG);
+
+        let mut message = MessageBuilder::default()
+            .body("Hello, world")
+            .max_tries(1)
+            .compose()
+            .unwrap();
+
+        manager
+            .queue("test_2")
+            .unwrap()
+            .database()
+            .await
+            .push(message.clone());
+
+        message.reserve(Utc::now().timestamp());
+        message.requeue(Utc::now().timestamp());
+        manager
+            .queue("test")
+            .unwrap()
+            .database()
+            .await
+            .push(message.clone());
+
+        execute_gc(&manager).await.unwrap();
+
+        let dead_letter = manager.queue("test_2").unwrap();
+        let mut dead_letter = dead_letter.database().await;
+
+        assert_eq!(dead_letter.size(), 2);
+        assert_eq!(*dead_letter.get(message.id()).unwrap().state().tries(), 0);
+
+        dead_letter.delete(message.id()).unwrap();
+
+        let moved = dead_letter.pop(Utc::now().timestamp()).unwrap();
+
+        assert_ne!(moved.id(), message.id());
+        assert_eq!(*moved.state().tries(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_gc_timeout() {
+        let manager = Manager::new(&DEAD_LETTER_CONFIG);
+
+        let mut message = MessageBuilder::default()
+            .body("Hello, world")
+            .max_tries(1)
+            .timeout(0)
+            .compose()
+            .unwrap();
+
+        message.reserve(Utc::now().timestamp() - 1);
+        manager
+            .queue("test")
+            .unwrap()
+            .database()
+            .await
+            .push(message.clone());
+
+        execute_gc(&manager).await.unwrap();
+
+        let dead_letter = manager.queue("test_2").unwrap();
+        let dead_letter = dead_letter.database().await;
+        let dead_message = dead_letter.get(message.id()).unwrap();
+
+        assert_eq!(
+            dead_message.history().attempts()[0].outcome(),
+            Some(Outcome::TimedOut)
+        );
     }
 }
@@ -4,6 +4,12 @@ pub mod gc;
 /// Persistence handler
 pub mod persistence;
 
+/// Timed out reservation handler
+pub mod reclaim;
+
+/// Recurring message schedule handler
+pub mod schedule;
+
 #[cfg(feature = "replication")]
 /// Replication job
 pub mod replication;
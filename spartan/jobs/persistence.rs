@@ -29,6 +29,7 @@ pub async fn spawn_persistence(manager: &Manager<'_>) {
 mod tests {
     use std::borrow::Cow;
 
+    use chrono::Utc;
     use spartan_lib::core::{
         dispatcher::{SimpleDispatcher, StatusAwareDispatcher},
         message::builder::MessageBuilder,
@@ -91,7 +92,7 @@ mod tests {
                 .unwrap()
                 .database()
                 .await
-                .pop()
+                .pop(Utc::now().timestamp())
                 .unwrap()
                 .body(),
             "Hello, world"
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use futures_util::{
+    stream::{iter, StreamExt},
+    TryStreamExt,
+};
+use tokio::time::delay_for;
+
+use crate::node::{persistence::PersistenceError, Manager};
+
+/// Concurrently iterates over all databases in node, and returns messages with timed out reservation back to queues.
+async fn execute_reclaim(manager: &Manager<'_>) -> Result<(), PersistenceError> {
+    iter(manager.node().iter())
+        .map(Ok)
+        .try_for_each_concurrent(None, |(name, queue)| async move {
+            manager.reclaim_expired(&name, &queue).await
+        })
+        .await
+}
+
+/// Reclaim job spawner
+///
+/// Periodically returns messages with timed out reservation back to queues.
+/// Only due entries of database timeout index are checked, so job runs much more often than GC.
+pub async fn spawn_reclaim(manager: &Manager<'_>) {
+    debug!("Spawning reclaim handler.");
+
+    let timer = Duration::from_secs(manager.config().reclaim_timer);
+
+    loop {
+        delay_for(timer).await;
+
+        if let Err(e) = execute_reclaim(manager).await {
+            error!("{}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::execute_reclaim;
+    use crate::{node::Manager, utils::testing::CONFIG};
+    use chrono::Utc;
+    use spartan_lib::core::{
+        dispatcher::{SimpleDispatcher, StatusAwareDispatcher},
+        message::{builder::MessageBuilder, Outcome},
+        payload::Identifiable,
+    };
+
+    #[tokio::test]
+    async fn test_reclaim() {
+        let manager = Manager::new(&CONFIG);
+        let queue = manager.queue("test").unwrap();
+
+        let timed = |timeout| {
+            MessageBuilder::default()
+                .body("Hello, world")
+                .max_tries(2)
+                .timeout(timeout)
+                .compose()
+                .unwrap()
+        };
+
+        let message = timed(0);
+        let pending = timed(600);
+        let now = Utc::now().timestamp();
+
+        {
+            let mut database = queue.database().await;
+
+            database.push(message.clone());
+            database.push(pending.clone());
+
+            // Reservation of first message is already timed out
+            database.pop(now - 1).unwrap();
+            database.pop(now).unwrap();
+        }
+
+        execute_reclaim(&manager).await.unwrap();
+
+        let mut database = queue.database().await;
+        let reclaimed = database.pop(now).unwrap();
+
+        assert_eq!(reclaimed.id(), message.id());
+        assert_eq!(
+            reclaimed.history().attempts()[0].outcome(),
+            Some(Outcome::TimedOut)
+        );
+        assert!(database.pop(now).is_none());
+    }
+}
@@ -20,11 +20,15 @@ use crate::{
 async fn replicate_manager(
     manager: &Manager<'_>,
     pool: &mut StreamPool<TcpStream>,
+    config: &Primary,
 ) -> PrimaryResult<()> {
     pool.ping().await?;
 
-    pool.ask()
-        .await?
+    let batch = pool.ask().await?;
+
+    batch.record_lag(manager, &config.destination).await;
+
+    batch
         .sync(manager)
         .await?
         .set_gc::<DefaultHasher>(manager)
@@ -45,7 +49,17 @@ async fn start_replication(
 
         info!("Starting database replication.");
 
-        match replicate_manager(manager, pool).await {
+        let result = replicate_manager(manager, pool, config).await;
+
+        manager.metrics().record_replication(match result {
+            Ok(_) => "success",
+            Err(PrimaryError::EmptySocket)
+            | Err(PrimaryError::SocketError(_))
+            | Err(PrimaryError::CodecError(_)) => "connection_error",
+            Err(_) => "error",
+        });
+
+        match result {
             Ok(_) => info!("Database replicated successfully!"),
             Err(PrimaryError::EmptySocket) => {
                 error!("Empty TCP socket");
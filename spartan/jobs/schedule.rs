@@ -0,0 +1,141 @@
+use std::{convert::TryInto, time::Duration};
+
+use chrono::Utc;
+use spartan_lib::core::message::Message;
+use tokio::time::delay_for;
+
+use crate::node::{persistence::PersistenceError, queue::PushError, Manager};
+
+/// Amount of seconds between schedule checks
+const SCHEDULE_TIMER: u64 = 1;
+
+/// Pushes messages of all due schedules to their queues.
+///
+/// Advanced schedules are persisted before messages are pushed,
+/// so run is never repeated after restart, regardless of queue deduplication window.
+/// Each run of schedule is also pushed with its own deduplication ID.
+async fn execute_schedules(manager: &Manager<'_>) -> Result<(), PersistenceError> {
+    let due = manager.schedules().await.take_due(Utc::now());
+
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    manager.persist_schedules().await?;
+
+    for push in due {
+        let queue = match manager.queue(&push.queue) {
+            Ok(queue) => queue,
+            Err(e) => {
+                warn!("Scheduled push to \"{}\" skipped: {}", push.queue, e);
+                continue;
+            }
+        };
+
+        let mut request = push.message;
+
+        if request.ttl.is_none() {
+            request.ttl = manager
+                .queue_config(&push.queue)
+                .and_then(|config| config.ttl);
+        }
+
+        request.deduplication_id = None;
+
+        let message: Message = match request.try_into() {
+            Ok(message) => message,
+            Err(e) => {
+                error!("Scheduled push to \"{}\" skipped: {}", push.queue, e);
+                continue;
+            }
+        };
+
+        match queue
+            .push(&push.queue, manager, message, Some(push.deduplication_id))
+            .await
+        {
+            Err(PushError::PersistenceError(e)) => return Err(e),
+            Err(e) => warn!("Scheduled push to \"{}\" skipped: {}", push.queue, e),
+            Ok(_) => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Schedule job spawner
+///
+/// Periodically pushes messages of due schedules.
+pub async fn spawn_schedules(manager: &Manager<'_>) {
+    debug!("Spawning schedule handler.");
+
+    let timer = Duration::from_secs(SCHEDULE_TIMER);
+
+    loop {
+        delay_for(timer).await;
+
+        if let Err(e) = execute_schedules(manager).await {
+            error!("{}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+    use once_cell::sync::Lazy;
+    use spartan_lib::core::{dispatcher::SimpleDispatcher, payload::Dispatchable};
+
+    use super::execute_schedules;
+    use crate::{
+        config::{schedule::ScheduleConfig, Config},
+        http::query::push::PushRequest,
+        node::Manager,
+    };
+
+    static SCHEDULE_CONFIG: Lazy<Config> = Lazy::new(|| Config {
+        schedules: Box::new([ScheduleConfig {
+            name: "test".into(),
+            queue: "test".into(),
+            cron: None,
+            interval: Some(60),
+            message: PushRequest {
+                body: "Hello, world".into(),
+                ..Default::default()
+            },
+        }]),
+        ..Default::default()
+    });
+
+    async fn rewind(manager: &Manager<'_>, next_run: DateTime<Utc>) {
+        let mut schedules = manager.schedules().await;
+        let mut schedule = schedules.remove("test", "test").unwrap();
+        schedule.next_run = next_run;
+        schedules.insert(schedule);
+    }
+
+    #[tokio::test]
+    async fn test_schedules() {
+        let manager = Manager::new(&SCHEDULE_CONFIG);
+        let queue = manager.queue("test").unwrap();
+
+        execute_schedules(&manager).await.unwrap();
+        assert_eq!(queue.database().await.size(), 0);
+
+        let next_run = Utc::now() - Duration::seconds(1);
+        rewind(&manager, next_run).await;
+        execute_schedules(&manager).await.unwrap();
+
+        assert_eq!(queue.database().await.size(), 1);
+        assert_eq!(
+            queue.database().await.peek().unwrap().body(),
+            "Hello, world"
+        );
+
+        // Repeated run, e.g. after restart, is discarded
+        rewind(&manager, next_run).await;
+        execute_schedules(&manager).await.unwrap();
+
+        assert_eq!(queue.database().await.size(), 1);
+    }
+}
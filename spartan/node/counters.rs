@@ -0,0 +1,47 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Queue operation, that is counted since start
+#[derive(Clone, Copy)]
+pub enum Counter {
+    Push,
+    Pop,
+    Requeue,
+    Delete,
+    Gc,
+}
+
+/// Counters of queue operations since start
+///
+/// Counters are not persisted, and are not affected by log replay or replication.
+#[derive(Default)]
+pub struct Counters([AtomicU64; 5]);
+
+impl Counters {
+    /// Add `amount` to counter
+    pub fn add(&self, counter: Counter, amount: usize) {
+        self.0[counter as usize].fetch_add(amount as u64, Ordering::Relaxed);
+    }
+
+    /// Get current counter value
+    pub fn get(&self, counter: Counter) -> u64 {
+        self.0[counter as usize].load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Counter, Counters};
+
+    #[test]
+    fn test_counters() {
+        let counters = Counters::default();
+
+        counters.add(Counter::Push, 3);
+        counters.add(Counter::Pop, 1);
+        counters.add(Counter::Push, 2);
+
+        assert_eq!(counters.get(Counter::Push), 5);
+        assert_eq!(counters.get(Counter::Pop), 1);
+        assert_eq!(counters.get(Counter::Gc), 0);
+    }
+}
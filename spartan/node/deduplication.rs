@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use spartan_lib::core::{message::Message, payload::Identifiable};
+
+use crate::node::event::Event;
+
+/// Default deduplication window in seconds
+pub const DEFAULT_DEDUPLICATION_WINDOW: u32 = 300;
+
+/// Time-bounded index of deduplication IDs of pushed messages
+///
+/// Each entry maps deduplication ID to message ID and UNIX timestamp, when entry expires.
+#[derive(Serialize, Deserialize, Default)]
+pub struct DeduplicationIndex {
+    entries: HashMap<Box<str>, (<Message as Identifiable>::Id, i64)>,
+}
+
+impl DeduplicationIndex {
+    /// Get ID of message, pushed with provided deduplication ID, if entry is not expired yet
+    pub fn get(&self, deduplication_id: &str) -> Option<<Message as Identifiable>::Id> {
+        let now = Utc::now().timestamp();
+
+        self.entries
+            .get(deduplication_id)
+            .filter(|(_, expires_at)| now < *expires_at)
+            .map(|(id, _)| *id)
+    }
+
+    /// Insert new index entry
+    pub fn insert(
+        &mut self,
+        deduplication_id: Box<str>,
+        id: <Message as Identifiable>::Id,
+        expires_at: i64,
+    ) {
+        self.entries.insert(deduplication_id, (id, expires_at));
+    }
+
+    /// Remove expired entries
+    pub fn prune(&mut self) {
+        let now = Utc::now().timestamp();
+        self.entries.retain(|_, (_, expires_at)| now < *expires_at);
+    }
+
+    /// Rebuild index entries from event log
+    pub fn apply_log<'a, L>(&mut self, log: L)
+    where
+        L: IntoIterator<Item = &'a Event<'static>>,
+    {
+        for event in log {
+            match event {
+                Event::Push(message, Some((deduplication_id, expires_at))) => {
+                    self.insert(deduplication_id.clone(), message.id(), *expires_at);
+                }
+                Event::PushBatch(_, entries) => {
+                    for (deduplication_id, id, expires_at) in entries {
+                        self.insert(deduplication_id.clone(), *id, *expires_at);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        self.prune();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use maybe_owned::MaybeOwned;
+    use spartan_lib::core::{message::builder::MessageBuilder, payload::Identifiable};
+    use uuid::Uuid;
+
+    use super::DeduplicationIndex;
+    use crate::node::event::Event;
+
+    #[test]
+    fn test_get() {
+        let mut index = DeduplicationIndex::default();
+        let id = Uuid::new_v4();
+        let now = Utc::now().timestamp();
+
+        index.insert("first".into(), id, now + 60);
+        index.insert("second".into(), Uuid::new_v4(), now);
+
+        assert_eq!(index.get("first"), Some(id));
+        assert_eq!(index.get("second"), None);
+        assert_eq!(index.get("third"), None);
+
+        index.prune();
+        assert_eq!(index.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_apply_log() {
+        let mut index = DeduplicationIndex::default();
+        let now = Utc::now().timestamp();
+
+        let message = MessageBuilder::default().body("first").compose().unwrap();
+        let id = message.id();
+
+        let events = vec![
+            Event::Push(MaybeOwned::Owned(message), Some(("first".into(), now + 60))),
+            Event::Push(
+                MaybeOwned::Owned(MessageBuilder::default().body("second").compose().unwrap()),
+                Some(("second".into(), now - 60)),
+            ),
+            Event::Pop(0),
+            Event::PushBatch(Vec::new(), vec![("third".into(), id, now + 60)]),
+        ];
+
+        index.apply_log(&events);
+
+        assert_eq!(index.entries.len(), 2);
+        assert_eq!(index.get("first"), Some(id));
+        assert_eq!(index.get("third"), Some(id));
+    }
+}
@@ -2,22 +2,44 @@ use maybe_owned::MaybeOwned;
 use serde::{Deserialize, Serialize};
 use spartan_lib::core::{
     dispatcher::{PositionBasedDelete, SimpleDispatcher, StatusAwareDispatcher},
-    message::Message,
+    message::{Message, Patch},
     payload::Identifiable,
 };
 
 /// Database event
 ///
-/// Only events that mutate database are present here
+/// Only events that mutate database are present here.
+/// Delays and times of status changes are stored as absolute UTC timestamps, so they are replayed as they were applied.
+///
+/// Checks of message delay, reservation timeout and TTL still use current time, so replay of [`Event::Pop`],
+/// requeue events and [`Event::Gc`] may skip or remove different messages, than they did when they were applied.
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(test, derive(Debug))]
 pub enum Event<'msg> {
-    Push(MaybeOwned<'msg, Message>),
-    Pop,
-    Requeue(<Message as Identifiable>::Id),
+    /// Pushed message, along with its deduplication ID and deduplication entry expiration time
+    Push(MaybeOwned<'msg, Message>, Option<(Box<str>, i64)>),
+    Pop(i64),
+    Requeue(<Message as Identifiable>::Id, i64),
     Delete(<Message as Identifiable>::Id),
     Gc,
     Clear,
+    RequeueDelayed(<Message as Identifiable>::Id, i64, i64),
+    Extend(<Message as Identifiable>::Id, Option<u32>, i64),
+    Nack(
+        <Message as Identifiable>::Id,
+        Option<Box<str>>,
+        Option<i64>,
+        i64,
+    ),
+    Reclaim(Vec<<Message as Identifiable>::Id>, i64),
+    PopBatch(u32, i64),
+    DeleteBatch(Vec<<Message as Identifiable>::Id>),
+    RequeueBatch(Vec<(<Message as Identifiable>::Id, Option<i64>)>, i64),
+    PushBatch(
+        Vec<MaybeOwned<'msg, Message>>,
+        Vec<(Box<str>, <Message as Identifiable>::Id, i64)>,
+    ),
+    Update(<Message as Identifiable>::Id, Patch),
 }
 
 #[cfg(feature = "replication")]
@@ -27,14 +49,35 @@ impl<'msg> Event<'msg> {
     /// If [`Event`] is of any variant but [`Event::Push`], then does nothing
     pub(super) fn into_owned(self) -> Event<'static> {
         match self {
-            Event::Push(message) => Event::Push(MaybeOwned::Owned(message.into_owned())),
+            Event::Push(message, deduplication) => {
+                Event::Push(MaybeOwned::Owned(message.into_owned()), deduplication)
+            }
             // These variants are needed to appease compiler
             // since it doesn't know that all other variants are 'static
-            Event::Pop => Event::Pop,
-            Event::Requeue(id) => Event::Requeue(id),
+            Event::Pop(now) => Event::Pop(now),
+            Event::Requeue(id, now) => Event::Requeue(id, now),
             Event::Delete(id) => Event::Delete(id),
             Event::Gc => Event::Gc,
             Event::Clear => Event::Clear,
+            Event::RequeueDelayed(id, available_at, now) => {
+                Event::RequeueDelayed(id, available_at, now)
+            }
+            Event::Extend(id, timeout, deadline) => Event::Extend(id, timeout, deadline),
+            Event::Nack(id, reason, available_at, now) => {
+                Event::Nack(id, reason, available_at, now)
+            }
+            Event::Reclaim(ids, now) => Event::Reclaim(ids, now),
+            Event::PopBatch(count, now) => Event::PopBatch(count, now),
+            Event::DeleteBatch(ids) => Event::DeleteBatch(ids),
+            Event::RequeueBatch(requeues, now) => Event::RequeueBatch(requeues, now),
+            Event::PushBatch(messages, deduplication) => Event::PushBatch(
+                messages
+                    .into_iter()
+                    .map(|message| MaybeOwned::Owned(message.into_owned()))
+                    .collect(),
+                deduplication,
+            ),
+            Event::Update(id, patch) => Event::Update(id, patch),
         }
     }
 }
@@ -42,10 +85,13 @@ impl<'msg> Event<'msg> {
 #[cfg(test)]
 impl PartialEq for Event<'_> {
     fn eq(&self, other: &Self) -> bool {
-        matches!((self, other), (Event::Pop, Event::Pop))
+        matches!((self, other), (Event::Pop(_), Event::Pop(_)))
     }
 }
 
+/// Database, that can be restored from log of [`Event`]
+///
+/// Replay result depends on time of replay, see [`Event`] for details.
 pub trait EventLog<L>: Default {
     fn from_log(log: L) -> Self {
         let mut database = Self::default();
@@ -67,17 +113,17 @@ where
     fn apply_log(&mut self, log: L) {
         for event in log {
             match event {
-                Event::Push(message) => match message {
+                Event::Push(message, _) => match message {
                     MaybeOwned::Owned(message) => self.push(message),
                     MaybeOwned::Borrowed(_) => {
                         panic!("Applying push event with borrowed message is not allowed.")
                     }
                 },
-                Event::Pop => {
-                    self.pop();
+                Event::Pop(now) => {
+                    self.pop(now);
                 }
-                Event::Requeue(id) => {
-                    self.requeue(id);
+                Event::Requeue(id, now) => {
+                    self.requeue(id, now);
                 }
                 Event::Delete(id) => {
                     self.delete(id);
@@ -88,6 +134,46 @@ where
                 Event::Clear => {
                     self.clear();
                 }
+                Event::RequeueDelayed(id, available_at, now) => {
+                    self.requeue_delayed(id, available_at, now);
+                }
+                Event::Extend(id, timeout, deadline) => {
+                    self.extend(id, timeout, deadline);
+                }
+                Event::Nack(id, reason, available_at, now) => {
+                    self.nack(id, reason, available_at, now);
+                }
+                Event::Reclaim(ids, now) => {
+                    for id in ids {
+                        self.time_out(id, now);
+                    }
+                }
+                Event::PopBatch(count, now) => {
+                    self.pop_many(count as usize, now);
+                }
+                Event::DeleteBatch(ids) => {
+                    for id in ids {
+                        self.delete(id);
+                    }
+                }
+                Event::RequeueBatch(requeues, now) => {
+                    for (id, available_at) in requeues {
+                        self.nack(id, None, available_at, now);
+                    }
+                }
+                Event::PushBatch(messages, _) => {
+                    for message in messages {
+                        match message {
+                            MaybeOwned::Owned(message) => self.push(message),
+                            MaybeOwned::Borrowed(_) => {
+                                panic!("Applying push event with borrowed message is not allowed.")
+                            }
+                        }
+                    }
+                }
+                Event::Update(id, patch) => {
+                    self.update(id, |message| message.apply(patch));
+                }
             }
         }
     }
@@ -95,9 +181,13 @@ where
 
 #[cfg(test)]
 mod tests {
+    use chrono::Utc;
     use maybe_owned::MaybeOwned;
     use spartan_lib::core::{
-        dispatcher::StatusAwareDispatcher, message::builder::MessageBuilder, payload::Identifiable,
+        db::Database,
+        dispatcher::{SimpleDispatcher, StatusAwareDispatcher},
+        message::{builder::MessageBuilder, Patch},
+        payload::{Dispatchable, Identifiable, Status},
     };
 
     use super::{Event, EventLog};
@@ -109,10 +199,182 @@ mod tests {
 
         let message = MessageBuilder::default().body("test").compose().unwrap();
 
-        let events = vec![Event::Push(MaybeOwned::Owned(message.clone()))];
+        let events = vec![Event::Push(MaybeOwned::Owned(message.clone()), None)];
 
         queue.database().await.apply_log(events);
 
-        assert_eq!(queue.database().await.pop().unwrap().id(), message.id());
+        assert_eq!(
+            queue
+                .database()
+                .await
+                .pop(Utc::now().timestamp())
+                .unwrap()
+                .id(),
+            message.id()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_batch_events() {
+        let queue = DB::default();
+        let now = Utc::now().timestamp();
+
+        let messages: Vec<_> = (0..3)
+            .map(|_| {
+                MessageBuilder::default()
+                    .body("test")
+                    .max_tries(2)
+                    .compose()
+                    .unwrap()
+            })
+            .collect();
+
+        let events = vec![
+            Event::PushBatch(
+                messages
+                    .iter()
+                    .map(|message| MaybeOwned::Owned(message.clone()))
+                    .collect(),
+                Vec::new(),
+            ),
+            Event::PopBatch(3, now),
+            Event::DeleteBatch(vec![messages[0].id()]),
+            Event::RequeueBatch(vec![(messages[1].id(), None)], now),
+        ];
+
+        queue.database().await.apply_log(events);
+
+        let mut database = queue.database().await;
+
+        assert_eq!(database.size(), 2);
+        assert_eq!(database.pop(now).unwrap().id(), messages[1].id());
+        assert!(database.pop(now).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_apply_requeue_delayed_event() {
+        let queue = DB::default();
+
+        let message = MessageBuilder::default()
+            .body("test")
+            .max_tries(2)
+            .compose()
+            .unwrap();
+
+        let now = Utc::now().timestamp();
+
+        // Delay, that already passed at the time of replay, must not postpone message again
+        let events = vec![
+            Event::Push(MaybeOwned::Owned(message.clone()), None),
+            Event::Pop(now - 2),
+            Event::RequeueDelayed(message.id(), now - 1, now - 2),
+        ];
+
+        queue.database().await.apply_log(events);
+
+        assert_eq!(
+            queue
+                .database()
+                .await
+                .pop(Utc::now().timestamp())
+                .unwrap()
+                .id(),
+            message.id()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_extend_event() {
+        let queue = DB::default();
+
+        let message = MessageBuilder::default().body("test").compose().unwrap();
+        let deadline = Utc::now().timestamp() + 600;
+
+        let events = vec![
+            Event::Push(MaybeOwned::Owned(message.clone()), None),
+            Event::Pop(Utc::now().timestamp()),
+            Event::Extend(message.id(), Some(300), deadline),
+        ];
+
+        queue.database().await.apply_log(events);
+
+        let database = queue.database().await;
+        let extended = database.get(message.id()).unwrap();
+
+        assert_eq!(extended.reservation_deadline(), Some(deadline));
+        assert_eq!(*extended.time().timeout().max(), 300);
+    }
+
+    #[tokio::test]
+    async fn test_apply_update_event() {
+        let queue = DB::default();
+
+        let message = MessageBuilder::default()
+            .body("test")
+            .delay(600)
+            .compose()
+            .unwrap();
+
+        let events = vec![
+            Event::Push(MaybeOwned::Owned(message.clone()), None),
+            Event::Update(
+                message.id(),
+                Patch {
+                    body: Some("updated".into()),
+                    available_at: Some(Utc::now().timestamp()),
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        queue.database().await.apply_log(events);
+
+        let mut database = queue.database().await;
+        let popped = database.pop(Utc::now().timestamp()).unwrap();
+
+        assert_eq!(popped.id(), message.id());
+        assert_eq!(*popped.body(), *"updated");
+    }
+
+    #[tokio::test]
+    async fn test_apply_history_events() {
+        let queue = DB::default();
+
+        let message = MessageBuilder::default()
+            .body("test")
+            .max_tries(3)
+            .timeout(3600)
+            .compose()
+            .unwrap();
+
+        let now = Utc::now().timestamp();
+
+        // Replayed history keeps times of original events
+        let events = vec![
+            Event::Push(MaybeOwned::Owned(message.clone()), None),
+            Event::Pop(now - 600),
+            Event::Requeue(message.id(), now - 500),
+            Event::Pop(now - 400),
+            Event::Reclaim(vec![message.id()], now - 300),
+        ];
+
+        queue.database().await.apply_log(events);
+
+        let database = queue.database().await;
+        let times: Vec<_> = database
+            .get(message.id())
+            .unwrap()
+            .history()
+            .attempts()
+            .iter()
+            .map(|attempt| {
+                (
+                    attempt.obtained_at().timestamp(),
+                    attempt.finished_at().unwrap().timestamp(),
+                )
+            })
+            .collect();
+
+        assert_eq!(times, [(now - 600, now - 500), (now - 400, now - 300)]);
     }
 }
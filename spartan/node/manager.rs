@@ -1,17 +1,34 @@
+use std::{collections::BTreeSet, sync::Arc, time::Instant};
+
+use chrono::Utc;
 use futures_util::{stream::iter, StreamExt, TryStreamExt};
+use spartan_lib::core::{
+    dispatcher::{PositionBasedDelete, StatusAwareDispatcher},
+    message::Message,
+    payload::{Identifiable, Status},
+};
 use thiserror::Error;
+use tokio::sync::{Mutex, MutexGuard};
 use warp::hyper::StatusCode;
 
 use crate::{
     actions::RespondableError,
-    config::{persistence::Persistence, Config},
+    config::{
+        persistence::{Persistence, PersistenceConfig},
+        queue::QueueConfig,
+        Config, RESERVED_QUEUE_NAMES,
+    },
     node::{
+        counters::Counter,
         event::Event,
+        metrics::Metrics,
         persistence::{
             log::Log,
-            snapshot::{PersistMode, Snapshot},
+            snapshot::{PersistMode, Snapshot, QUEUES_FILE, SCHEDULES_FILE},
             PersistenceError,
         },
+        queue::PushError,
+        schedule::Schedules,
         Node, DB,
     },
 };
@@ -20,11 +37,28 @@ use crate::{
 pub enum ManagerError {
     #[error("Queue not found")]
     QueueNotFound,
+    #[error("Queue already exists")]
+    QueueAlreadyExists,
+    #[error("Queue name may only contain alphanumeric characters, dashes and underscores, and must not be reserved")]
+    InvalidQueueName,
+    #[error("Queue is declared in config")]
+    ConfigQueue,
+    #[error("Queues can't be created or deleted at runtime while replication is enabled")]
+    ReplicationEnabled,
+    #[error(transparent)]
+    PersistenceError(#[from] PersistenceError),
 }
 
 impl RespondableError for ManagerError {
     fn status_code(&self) -> StatusCode {
-        StatusCode::NOT_FOUND
+        match self {
+            ManagerError::QueueNotFound => StatusCode::NOT_FOUND,
+            ManagerError::QueueAlreadyExists
+            | ManagerError::ConfigQueue
+            | ManagerError::ReplicationEnabled => StatusCode::CONFLICT,
+            ManagerError::InvalidQueueName => StatusCode::BAD_REQUEST,
+            ManagerError::PersistenceError(e) => e.status_code(),
+        }
     }
 }
 
@@ -34,30 +68,298 @@ pub struct Manager<'c> {
     config: &'c Config<'c>,
 
     /// Node
-    node: Node<'c>,
+    node: Node,
+
+    /// Names of queues, that were created at runtime
+    queues: Mutex<BTreeSet<Box<str>>>,
+
+    /// Recurring message schedules
+    schedules: Mutex<Schedules>,
+
+    /// Server metrics
+    metrics: Metrics,
 }
 
 impl<'c> Manager<'c> {
     /// Create new manager without node
     pub fn new(config: &'c Config) -> Manager<'c> {
-        let mut node = Node::default();
+        let node = Node::default();
         node.load_from_config(config);
-        Manager { config, node }
+
+        Manager {
+            config,
+            node,
+            queues: Mutex::new(BTreeSet::new()),
+            schedules: Mutex::new(Schedules::from_config(&config.schedules)),
+            metrics: Metrics::default(),
+        }
     }
 
     /// Obtain queue from local node
-    pub fn queue(&self, name: &str) -> Result<&DB, ManagerError> {
+    pub fn queue(&self, name: &str) -> Result<Arc<DB>, ManagerError> {
         self.node.queue(name).ok_or(ManagerError::QueueNotFound)
     }
 
+    /// Obtain configuration of provided queue
+    pub fn queue_config(&self, name: &str) -> Option<&'c QueueConfig> {
+        self.config.queue_config.get(name)
+    }
+
+    /// Obtain dead letter queue of provided queue
+    ///
+    /// Returns [`None`] if queue has no dead letter queue configured, or if configured queue doesn't exist
+    pub fn dead_letter_queue(&self, name: &str) -> Option<(&'c str, Arc<DB>)> {
+        let dead_letter = self.queue_config(name)?.dead_letter.as_deref()?;
+
+        match self.node.queue(dead_letter) {
+            Some(queue) => Some((dead_letter, queue)),
+            None => {
+                warn!(
+                    "Dead letter queue \"{}\" of \"{}\" not found",
+                    dead_letter, name
+                );
+                None
+            }
+        }
+    }
+
+    /// Remove messages from `name` queue and move them to its dead letter queue
+    ///
+    /// Each message is logged as [`Event::Delete`] in source queue before it's removed,
+    /// and as [`Event::Push`] in dead letter queue, so message state survives restart and replication.
+    ///
+    /// Message, whose ID is already taken in dead letter queue, is moved with newly generated ID.
+    ///
+    /// If queue has no dead letter queue, then messages are only removed.
+    pub async fn dead_letter(
+        &self,
+        name: &str,
+        queue: &DB,
+        ids: Vec<<Message as Identifiable>::Id>,
+    ) -> Result<(), PersistenceError> {
+        let dead_letter = self.dead_letter_queue(name);
+
+        for id in ids {
+            queue.log_event(name, self, Event::Delete(id)).await?;
+
+            let message = queue.database().await.delete(id);
+
+            let (dead_letter_name, dead_letter, mut message) = match (&dead_letter, message) {
+                (Some((dead_letter_name, dead_letter)), Some(message)) => {
+                    (*dead_letter_name, dead_letter, message)
+                }
+                _ => continue,
+            };
+
+            info!(
+                "Moving message {} from \"{}\" to dead letter queue \"{}\"",
+                id, name, dead_letter_name
+            );
+
+            message.dead_letter(Utc::now().timestamp());
+
+            loop {
+                match dead_letter
+                    .push(dead_letter_name, self, message.clone(), None)
+                    .await
+                {
+                    Ok(_) => break,
+                    Err(PushError::MessageConflict) => {
+                        let id = message.id();
+                        message.regenerate_id();
+
+                        warn!(
+                            "Message {} already exists in dead letter queue \"{}\", moving it as {}",
+                            id,
+                            dead_letter_name,
+                            message.id()
+                        );
+                    }
+                    Err(PushError::PersistenceError(e)) => return Err(e),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove expired messages, that block `name` queue from dispatching other messages
+    ///
+    /// Removed messages are moved to dead letter queue, if there is any.
+    pub async fn drain_expired(&self, name: &str, queue: &DB) -> Result<(), PersistenceError> {
+        let expired = queue.database().await.expired_ids(Utc::now().timestamp());
+
+        if expired.is_empty() {
+            Ok(())
+        } else {
+            queue.counters().add(Counter::Gc, expired.len());
+            self.dead_letter(name, queue, expired).await
+        }
+    }
+
+    /// Return messages of `name` queue, whose reservation timed out, back to queue
+    ///
+    /// Timed out messages are found and logged as single [`Event::Reclaim`] before they are returned,
+    /// so database is not locked while event is logged.
+    /// Messages, that were requeued or deleted meanwhile, are skipped.
+    pub async fn reclaim_expired(&self, name: &str, queue: &DB) -> Result<(), PersistenceError> {
+        let now = Utc::now().timestamp();
+
+        let expired = queue.database().await.timed_out_ids(now);
+
+        if expired.is_empty() {
+            return Ok(());
+        }
+
+        queue
+            .log_event(name, self, Event::Reclaim(expired.clone(), now))
+            .await?;
+
+        let mut database = queue.database().await;
+
+        let reclaimed = expired
+            .into_iter()
+            .filter_map(|id| database.time_out(id, now))
+            .count();
+
+        debug!("Reclaimed {} timed out messages of \"{}\"", reclaimed, name);
+
+        Ok(())
+    }
+
     pub fn config(&self) -> &'c Config<'c> {
         &self.config
     }
 
-    pub fn node(&self) -> &Node<'_> {
+    pub fn node(&self) -> &Node {
         &self.node
     }
 
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Obtain names of queues, that were created at runtime
+    pub async fn queues(&self) -> MutexGuard<'_, BTreeSet<Box<str>>> {
+        self.queues.lock().await
+    }
+
+    /// Create empty queue at runtime
+    ///
+    /// Created queue uses default queue configuration.
+    /// Leftover persisted files of queue with the same name are removed beforehand.
+    ///
+    /// Queues can't be created while replication is enabled, as replicas don't receive them.
+    pub async fn create_queue(&self, name: &str) -> Result<(), ManagerError> {
+        if self.config.replication.is_some() {
+            return Err(ManagerError::ReplicationEnabled);
+        }
+
+        if name.is_empty()
+            || RESERVED_QUEUE_NAMES.contains(&name)
+            || !name
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_')
+        {
+            return Err(ManagerError::InvalidQueueName);
+        }
+
+        let mut queues = self.queues().await;
+
+        if self.node.queue(name).is_some() {
+            return Err(ManagerError::QueueAlreadyExists);
+        }
+
+        self.remove_queue_files(name).await?;
+
+        self.node.add(name);
+        queues.insert(name.into());
+
+        self.persist_queues(&queues).await?;
+
+        Ok(())
+    }
+
+    /// Delete queue, that was created at runtime, along with its persisted files
+    ///
+    /// Queues, that are declared in config, can't be deleted, as well as any queue while replication is enabled.
+    ///
+    /// Jobs and actions, that still hold deleted queue, no longer write its files.
+    pub async fn delete_queue(&self, name: &str) -> Result<(), ManagerError> {
+        if self.config.queues.iter().any(|queue| &**queue == name) {
+            return Err(ManagerError::ConfigQueue);
+        }
+
+        if self.config.replication.is_some() {
+            return Err(ManagerError::ReplicationEnabled);
+        }
+
+        let mut queues = self.queues().await;
+
+        if !queues.remove(name) {
+            return Err(ManagerError::QueueNotFound);
+        }
+
+        let queue = self.node.remove(name);
+
+        self.persist_queues(&queues).await?;
+
+        if let Some(queue) = queue {
+            let _removed = queue.mark_removed().await;
+            self.remove_queue_files(name).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Persist names of queues, that were created at runtime, if log persistence is used
+    ///
+    /// With snapshot persistence, queue names are persisted along with queues
+    async fn persist_queues(&self, queues: &BTreeSet<Box<str>>) -> Result<(), PersistenceError> {
+        if let Some(config) = self
+            .config
+            .persistence
+            .as_ref()
+            .filter(|config| matches!(config.mode, Persistence::Log))
+        {
+            Snapshot::new(config).persist(queues, QUEUES_FILE).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Remove persisted files of queue, if there are any
+    async fn remove_queue_files(&self, name: &str) -> Result<(), PersistenceError> {
+        if let Some(config) = self.config.persistence.as_ref() {
+            Snapshot::new(config).remove_queue(name).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Obtain recurring message schedules
+    pub async fn schedules(&self) -> MutexGuard<'_, Schedules> {
+        self.schedules.lock().await
+    }
+
+    /// Persist schedules, if log persistence is used
+    ///
+    /// With snapshot persistence, schedules are persisted along with queues
+    pub async fn persist_schedules(&self) -> Result<(), PersistenceError> {
+        if let Some(config) = self
+            .config
+            .persistence
+            .as_ref()
+            .filter(|config| matches!(config.mode, Persistence::Log))
+        {
+            Snapshot::new(config)
+                .persist(&*self.schedules().await, SCHEDULES_FILE)
+                .await
+        } else {
+            Ok(())
+        }
+    }
+
     pub async fn load_from_fs(&mut self) -> Result<(), PersistenceError> {
         if let Some(config) = self.config.persistence.as_ref() {
             match config.mode {
@@ -78,8 +380,49 @@ impl<'c> Manager<'c> {
                     }
                 }
             }
+
+            self.load_queues(config).await?;
+
+            let schedules = Snapshot::new(config).load_schedules().await?;
+            self.schedules().await.restore(schedules);
+        }
+
+        Ok(())
+    }
+
+    /// Load queues, that were created at runtime, and merge them with queues declared in config
+    ///
+    /// Runtime queue without persisted files is loaded empty,
+    /// as with snapshot persistence it may be created after the latest snapshot.
+    async fn load_queues(&self, config: &PersistenceConfig<'_>) -> Result<(), PersistenceError> {
+        let persisted = Snapshot::new(config).load_queues().await?;
+
+        for name in persisted.iter() {
+            if self.config.queues.contains(name) {
+                continue;
+            }
+
+            let queue = match config.mode {
+                Persistence::Log => Log::new(config).load_queue(&**name).await,
+                Persistence::Snapshot => Snapshot::new(config).load_queue(&**name).await,
+            };
+
+            match queue {
+                Ok(queue) => self.node.add_db(name, queue),
+                Err(PersistenceError::FileOpenError(e)) => {
+                    error!("{}", e);
+                    self.node.add(name);
+                }
+                Err(e) => return Err(e),
+            }
         }
 
+        self.queues().await.extend(
+            persisted
+                .into_iter()
+                .filter(|name| !self.config.queues.contains(name)),
+        );
+
         Ok(())
     }
 
@@ -91,13 +434,28 @@ impl<'c> Manager<'c> {
             };
 
             let driver = &Snapshot::new(config);
+            let started = Instant::now();
 
-            iter(self.node.iter())
-                .map(Ok)
-                .try_for_each_concurrent(None, move |(name, db)| {
-                    driver.persist_queue(name, db, mode)
-                })
-                .await
+            let result = async {
+                driver
+                    .persist(&*self.schedules().await, SCHEDULES_FILE)
+                    .await?;
+
+                driver.persist(&*self.queues().await, QUEUES_FILE).await?;
+
+                iter(self.node.iter())
+                    .map(Ok)
+                    .try_for_each_concurrent(None, move |(name, db)| async move {
+                        driver.persist_queue(&*name, &db, mode).await
+                    })
+                    .await
+            }
+            .await;
+
+            self.metrics
+                .observe_persistence("snapshot", started.elapsed(), result.is_ok());
+
+            result
         } else {
             Ok(())
         }
@@ -110,7 +468,13 @@ impl<'c> Manager<'c> {
             .as_ref()
             .filter(|config| matches!(config.mode, Persistence::Log))
         {
-            Log::new(config).persist_event(event, queue).await
+            let started = Instant::now();
+            let result = Log::new(config).persist_event(event, queue).await;
+
+            self.metrics
+                .observe_persistence("log", started.elapsed(), result.is_ok());
+
+            result
         } else {
             Ok(())
         }
@@ -131,7 +495,7 @@ impl<'c> Manager<'c> {
 
 #[cfg(test)]
 impl<'c> Manager<'c> {
-    pub fn node_mut(&mut self) -> &mut Node<'c> {
+    pub fn node_mut(&mut self) -> &mut Node {
         &mut self.node
     }
 }
@@ -150,9 +514,14 @@ mod tests {
     use crate::{
         config::{
             persistence::{Persistence, PersistenceConfig},
+            schedule::ScheduleConfig,
             Config,
         },
-        node::event::Event,
+        node::{
+            event::Event,
+            persistence::snapshot::{PersistMode, Snapshot},
+            schedule::Schedule,
+        },
     };
 
     #[tokio::test]
@@ -197,6 +566,47 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_delete_queue_files() {
+        let dir = TempDir::new().unwrap();
+
+        let config = Config {
+            persistence: Some(PersistenceConfig {
+                mode: Persistence::Log,
+                path: Cow::Borrowed(dir.path()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let manager = Manager::new(&config);
+        manager.create_queue("created").await.unwrap();
+
+        let queue = manager.queue("created").unwrap();
+
+        queue
+            .log_event("created", &manager, Event::Clear)
+            .await
+            .unwrap();
+        assert!(dir.path().join("created").exists());
+
+        manager.delete_queue("created").await.unwrap();
+        assert!(!dir.path().join("created").exists());
+
+        // Deleted queue may still be held by jobs, that started before deletion
+        queue
+            .log_event("created", &manager, Event::Clear)
+            .await
+            .unwrap();
+
+        Snapshot::new(config.persistence.as_ref().unwrap())
+            .persist_queue("created", &queue, PersistMode::Queue)
+            .await
+            .unwrap();
+
+        assert!(!dir.path().join("created").exists());
+    }
+
     async fn load_log(compaction: bool) {
         let dir = TempDir::new().unwrap();
 
@@ -217,12 +627,15 @@ mod tests {
             manager
                 .log(
                     "test",
-                    &Event::Push(MaybeOwned::Owned(
-                        MessageBuilder::default()
-                            .body("Hello, world")
-                            .compose()
-                            .unwrap(),
-                    )),
+                    &Event::Push(
+                        MaybeOwned::Owned(
+                            MessageBuilder::default()
+                                .body("Hello, world")
+                                .compose()
+                                .unwrap(),
+                        ),
+                        None,
+                    ),
                 )
                 .await
                 .unwrap();
@@ -270,4 +683,112 @@ mod tests {
     async fn test_load_log_compaction() {
         load_log(true).await;
     }
+
+    async fn load_runtime_queues(mode: Persistence) {
+        let dir = TempDir::new().unwrap();
+
+        let config = Config {
+            persistence: Some(PersistenceConfig {
+                mode,
+                path: Cow::Borrowed(dir.path()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        {
+            let manager = Manager::new(&config);
+
+            manager.create_queue("created").await.unwrap();
+            manager.create_queue("deleted").await.unwrap();
+
+            let queue = manager.queue("created").unwrap();
+            let message = MessageBuilder::default()
+                .body("Hello, world")
+                .compose()
+                .unwrap();
+
+            queue
+                .log_event(
+                    "created",
+                    &manager,
+                    Event::Push(MaybeOwned::Borrowed(&message), None),
+                )
+                .await
+                .unwrap();
+            queue.database().await.push(message);
+
+            manager.delete_queue("deleted").await.unwrap();
+            manager.snapshot().await.unwrap();
+        }
+
+        let mut manager = Manager::new(&config);
+        manager.load_from_fs().await.unwrap();
+
+        assert_eq!(
+            manager
+                .queue("created")
+                .unwrap()
+                .database()
+                .await
+                .peek()
+                .unwrap()
+                .body(),
+            "Hello, world"
+        );
+        assert!(manager.queue("deleted").is_err());
+        assert!(manager.queue("test").is_ok());
+        assert_eq!(manager.queues().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_load_runtime_queues_log() {
+        load_runtime_queues(Persistence::Log).await;
+    }
+
+    #[tokio::test]
+    async fn test_load_runtime_queues_snapshot() {
+        load_runtime_queues(Persistence::Snapshot).await;
+    }
+
+    #[tokio::test]
+    async fn test_load_schedules() {
+        let dir = TempDir::new().unwrap();
+
+        let config = Config {
+            persistence: Some(PersistenceConfig {
+                mode: Persistence::Log,
+                path: Cow::Borrowed(dir.path()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let schedule = ScheduleConfig {
+            name: "test".into(),
+            queue: "test".into(),
+            cron: None,
+            interval: Some(60),
+            message: Default::default(),
+        };
+
+        let next_run = {
+            let manager = Manager::new(&config);
+            let schedule = Schedule::new(schedule, false).unwrap();
+            let next_run = schedule.next_run;
+
+            manager.schedules().await.insert(schedule);
+            manager.persist_schedules().await.unwrap();
+
+            next_run
+        };
+
+        let mut manager = Manager::new(&config);
+        manager.load_from_fs().await.unwrap();
+
+        let schedules = manager.schedules().await;
+        let schedule = schedules.queue("test").next().unwrap();
+
+        assert_eq!(schedule.next_run, next_run);
+    }
 }
@@ -0,0 +1,389 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Write},
+    sync::Mutex,
+    time::Duration,
+};
+
+use crate::node::counters::{Counter, Counters};
+
+/// Upper bounds of latency histogram buckets in seconds
+const BUCKETS: [f64; 12] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// Labels of operation counters, exported from queue [`Counters`]
+const OPERATIONS: [(Counter, &str); 5] = [
+    (Counter::Push, "push"),
+    (Counter::Pop, "pop"),
+    (Counter::Requeue, "requeue"),
+    (Counter::Delete, "delete"),
+    (Counter::Gc, "gc"),
+];
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+
+        for (bucket, bound) in self.buckets.iter_mut().zip(BUCKETS.iter()) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    fn write(&self, out: &mut String, name: &str, labels: &[(&str, &str)]) {
+        for (bucket, bound) in self.buckets.iter().zip(BUCKETS.iter()) {
+            let bound = bound.to_string();
+            let mut labels = labels.to_vec();
+            labels.push(("le", &bound));
+            write_sample(out, &format!("{}_bucket", name), &labels, bucket);
+        }
+
+        let mut inf = labels.to_vec();
+        inf.push(("le", "+Inf"));
+        write_sample(out, &format!("{}_bucket", name), &inf, self.count);
+        write_sample(out, &format!("{}_sum", name), labels, self.sum);
+        write_sample(out, &format!("{}_count", name), labels, self.count);
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    actions: BTreeMap<&'static str, Histogram>,
+    responses: BTreeMap<(&'static str, u16), u64>,
+    gc: BTreeMap<Box<str>, Histogram>,
+    persistence: BTreeMap<&'static str, Histogram>,
+    persistence_errors: BTreeMap<&'static str, u64>,
+    #[cfg(feature = "replication")]
+    replication: BTreeMap<&'static str, u64>,
+    #[cfg(feature = "replication")]
+    replication_lag: BTreeMap<(Box<str>, Box<str>), u64>,
+}
+
+/// State of single queue at the moment of scrape
+pub struct QueueSample<'a> {
+    pub name: &'a str,
+    pub size: usize,
+    pub in_flight: usize,
+    pub counters: &'a Counters,
+}
+
+/// Server metrics, exported in Prometheus text format
+///
+/// Metrics are kept in memory only, and are reset on restart.
+#[derive(Default)]
+pub struct Metrics(Mutex<Registry>);
+
+impl Metrics {
+    /// Record handled request of action
+    pub fn observe_action(&self, action: &'static str, duration: Duration, status: u16) {
+        let mut registry = self.0.lock().unwrap();
+
+        registry
+            .actions
+            .entry(action)
+            .or_default()
+            .observe(duration);
+
+        *registry.responses.entry((action, status)).or_default() += 1;
+    }
+
+    /// Record GC cycle of queue
+    pub fn observe_gc(&self, queue: &str, duration: Duration) {
+        let mut registry = self.0.lock().unwrap();
+
+        match registry.gc.get_mut(queue) {
+            Some(histogram) => histogram.observe(duration),
+            None => {
+                let mut histogram = Histogram::default();
+                histogram.observe(duration);
+                registry.gc.insert(queue.into(), histogram);
+            }
+        }
+    }
+
+    /// Record persistence write of provided driver
+    pub fn observe_persistence(&self, driver: &'static str, duration: Duration, success: bool) {
+        let mut registry = self.0.lock().unwrap();
+
+        registry
+            .persistence
+            .entry(driver)
+            .or_default()
+            .observe(duration);
+
+        if !success {
+            *registry.persistence_errors.entry(driver).or_default() += 1;
+        }
+    }
+
+    /// Record result of replication round
+    #[cfg(feature = "replication")]
+    pub fn record_replication(&self, result: &'static str) {
+        *self
+            .0
+            .lock()
+            .unwrap()
+            .replication
+            .entry(result)
+            .or_default() += 1;
+    }
+
+    /// Set amount of events, that replica was missing at the start of replication round
+    #[cfg(feature = "replication")]
+    pub fn set_replication_lag(&self, replica: &str, queue: &str, lag: u64) {
+        self.0
+            .lock()
+            .unwrap()
+            .replication_lag
+            .insert((replica.into(), queue.into()), lag);
+    }
+
+    /// Render queue samples and recorded metrics in Prometheus text format
+    pub fn render(&self, queues: &[QueueSample]) -> String {
+        let mut out = String::new();
+
+        write_family(
+            &mut out,
+            "spartan_queue_messages",
+            "gauge",
+            "Amount of messages in queue",
+        );
+        for queue in queues {
+            write_sample(
+                &mut out,
+                "spartan_queue_messages",
+                &[("queue", queue.name)],
+                queue.size,
+            );
+        }
+
+        write_family(
+            &mut out,
+            "spartan_queue_in_flight_messages",
+            "gauge",
+            "Amount of reserved messages in queue",
+        );
+        for queue in queues {
+            write_sample(
+                &mut out,
+                "spartan_queue_in_flight_messages",
+                &[("queue", queue.name)],
+                queue.in_flight,
+            );
+        }
+
+        write_family(
+            &mut out,
+            "spartan_queue_operations_total",
+            "counter",
+            "Amount of queue operations since start",
+        );
+        for queue in queues {
+            for (counter, operation) in OPERATIONS.iter() {
+                write_sample(
+                    &mut out,
+                    "spartan_queue_operations_total",
+                    &[("queue", queue.name), ("operation", operation)],
+                    queue.counters.get(*counter),
+                );
+            }
+        }
+
+        let registry = self.0.lock().unwrap();
+
+        write_family(
+            &mut out,
+            "spartan_action_duration_seconds",
+            "histogram",
+            "Latency of handled action requests",
+        );
+        for (action, histogram) in registry.actions.iter() {
+            histogram.write(
+                &mut out,
+                "spartan_action_duration_seconds",
+                &[("action", action)],
+            );
+        }
+
+        write_family(
+            &mut out,
+            "spartan_action_requests_total",
+            "counter",
+            "Amount of handled action requests by response status",
+        );
+        for ((action, status), count) in registry.responses.iter() {
+            write_sample(
+                &mut out,
+                "spartan_action_requests_total",
+                &[("action", action), ("status", &status.to_string())],
+                count,
+            );
+        }
+
+        write_family(
+            &mut out,
+            "spartan_gc_duration_seconds",
+            "histogram",
+            "Duration of GC cycles",
+        );
+        for (queue, histogram) in registry.gc.iter() {
+            histogram.write(&mut out, "spartan_gc_duration_seconds", &[("queue", queue)]);
+        }
+
+        write_family(
+            &mut out,
+            "spartan_persistence_write_duration_seconds",
+            "histogram",
+            "Latency of persistence writes",
+        );
+        for (driver, histogram) in registry.persistence.iter() {
+            histogram.write(
+                &mut out,
+                "spartan_persistence_write_duration_seconds",
+                &[("driver", driver)],
+            );
+        }
+
+        write_family(
+            &mut out,
+            "spartan_persistence_errors_total",
+            "counter",
+            "Amount of failed persistence writes",
+        );
+        for (driver, count) in registry.persistence_errors.iter() {
+            write_sample(
+                &mut out,
+                "spartan_persistence_errors_total",
+                &[("driver", driver)],
+                count,
+            );
+        }
+
+        #[cfg(feature = "replication")]
+        {
+            write_family(
+                &mut out,
+                "spartan_replication_rounds_total",
+                "counter",
+                "Amount of replication rounds by result",
+            );
+            for (result, count) in registry.replication.iter() {
+                write_sample(
+                    &mut out,
+                    "spartan_replication_rounds_total",
+                    &[("result", result)],
+                    count,
+                );
+            }
+
+            write_family(
+                &mut out,
+                "spartan_replication_lag_events",
+                "gauge",
+                "Amount of events, that replica was missing at the start of the latest replication round",
+            );
+            for ((replica, queue), lag) in registry.replication_lag.iter() {
+                write_sample(
+                    &mut out,
+                    "spartan_replication_lag_events",
+                    &[("replica", replica), ("queue", queue)],
+                    lag,
+                );
+            }
+        }
+
+        out
+    }
+}
+
+fn write_family(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+}
+
+fn write_sample<V>(out: &mut String, name: &str, labels: &[(&str, &str)], value: V)
+where
+    V: Display,
+{
+    out.push_str(name);
+
+    if !labels.is_empty() {
+        out.push('{');
+
+        for (index, (label, value)) in labels.iter().enumerate() {
+            if index > 0 {
+                out.push(',');
+            }
+
+            write!(out, "{}=\"", label).unwrap();
+
+            for char in value.chars() {
+                match char {
+                    '\\' => out.push_str("\\\\"),
+                    '"' => out.push_str("\\\""),
+                    '\n' => out.push_str("\\n"),
+                    char => out.push(char),
+                }
+            }
+
+            out.push('"');
+        }
+
+        out.push('}');
+    }
+
+    writeln!(out, " {}", value).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Metrics, QueueSample};
+    use crate::node::counters::{Counter, Counters};
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        let counters = Counters::default();
+
+        counters.add(Counter::Push, 2);
+        metrics.observe_action("push", Duration::from_millis(2), 200);
+        metrics.observe_action("push", Duration::from_secs(5), 404);
+        metrics.observe_persistence("log", Duration::from_millis(1), false);
+
+        let output = metrics.render(&[QueueSample {
+            name: "te\"st",
+            size: 3,
+            in_flight: 1,
+            counters: &counters,
+        }]);
+
+        assert!(output.contains("# TYPE spartan_queue_messages gauge\n"));
+        assert!(output.contains("spartan_queue_messages{queue=\"te\\\"st\"} 3\n"));
+        assert!(output.contains("spartan_queue_in_flight_messages{queue=\"te\\\"st\"} 1\n"));
+        assert!(output
+            .contains("spartan_queue_operations_total{queue=\"te\\\"st\",operation=\"push\"} 2\n"));
+        assert!(output
+            .contains("spartan_action_duration_seconds_bucket{action=\"push\",le=\"0.0025\"} 1\n"));
+        assert!(output
+            .contains("spartan_action_duration_seconds_bucket{action=\"push\",le=\"+Inf\"} 2\n"));
+        assert!(output.contains("spartan_action_duration_seconds_count{action=\"push\"} 2\n"));
+        assert!(
+            output.contains("spartan_action_requests_total{action=\"push\",status=\"404\"} 1\n")
+        );
+        assert!(output.contains("spartan_persistence_errors_total{driver=\"log\"} 1\n"));
+    }
+}
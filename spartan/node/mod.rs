@@ -10,11 +10,26 @@ pub mod persistence;
 /// Database event
 pub mod event;
 
+/// Push deduplication index
+pub mod deduplication;
+
+/// Queue operation counters
+pub mod counters;
+
+/// Prometheus metrics
+pub mod metrics;
+
+/// Recurring message schedules
+pub mod schedule;
+
 #[cfg(feature = "replication")]
 /// Database replication
 pub mod replication;
 
-use std::collections::{hash_map::RandomState, HashMap};
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    sync::{Arc, RwLock},
+};
 
 pub use manager::Manager;
 pub use queue::Queue;
@@ -27,36 +42,54 @@ use crate::node::replication::storage::ReplicationStorage;
 pub type DB = Queue<TreeDatabase<Message>>;
 
 /// Key-value node implementation
+///
+/// Queues may be added and removed at runtime, so each queue is shared with [`Arc`].
 #[derive(Default)]
-pub struct Node<'c, S = RandomState> {
+pub struct Node<S = RandomState> {
     /// Node database
-    db: HashMap<&'c str, DB, S>,
+    db: RwLock<HashMap<Arc<str>, Arc<DB>, S>>,
 }
 
-impl<'c> Node<'c> {
+impl Node {
     /// Get node queue entry
-    pub fn queue(&self, name: &str) -> Option<&DB> {
-        self.db.get(name)
+    pub fn queue(&self, name: &str) -> Option<Arc<DB>> {
+        self.db.read().unwrap().get(name).cloned()
     }
 
     /// Add default queue entry to node
-    pub fn add(&mut self, name: &'c str) {
+    pub fn add(&self, name: &str) {
         self.add_db(name, DB::default())
     }
 
     /// Add queue entry to node
-    pub fn add_db(&mut self, name: &'c str, db: DB) {
+    ///
+    /// Existing queue with the same name is replaced.
+    pub fn add_db(&self, name: &str, db: DB) {
         info!("Initializing queue \"{}\"", name);
-        self.db.insert(name, db);
+        self.db.write().unwrap().insert(name.into(), Arc::new(db));
+    }
+
+    /// Remove queue entry from node
+    pub fn remove(&self, name: &str) -> Option<Arc<DB>> {
+        info!("Removing queue \"{}\"", name);
+        self.db.write().unwrap().remove(name)
     }
 
     /// Iterate over queues in Node
-    pub fn iter(&'c self) -> impl Iterator<Item = (&'c str, &'c DB)> {
-        self.db.iter().map(|(name, db)| (*name, db))
+    ///
+    /// Queues are collected beforehand, so queues, that were added or removed during iteration, are not affected.
+    pub fn iter(&self) -> impl Iterator<Item = (Arc<str>, Arc<DB>)> {
+        self.db
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, db)| (name.clone(), db.clone()))
+            .collect::<Vec<_>>()
+            .into_iter()
     }
 
     /// Load queues from config
-    pub fn load_from_config(&mut self, config: &'c Config) {
+    pub fn load_from_config(&self, config: &Config) {
         config.queues.iter().for_each(|queue| self.add(queue));
     }
 
@@ -19,8 +19,13 @@ use crate::node::persistence::snapshot::REPLICATION_FILE as SNAPSHOT_REPLICATION
 use crate::{
     config::persistence::PersistenceConfig,
     node::{
+        deduplication::DeduplicationIndex,
         event::{Event, EventLog},
-        persistence::{snapshot::Snapshot, PersistenceError},
+        persistence::{
+            check_header, make_header,
+            snapshot::{Snapshot, DEDUPLICATION_FILE},
+            PersistenceError, HEADER_SIZE,
+        },
         Queue,
     },
 };
@@ -87,6 +92,8 @@ impl<'c> Log<'c> {
     }
 
     /// Get buffer of log entries from byte source
+    ///
+    /// Non-empty source must start with format header, that is followed by log entries.
     async fn parse_log<T, S>(source: &mut S) -> Result<Vec<T>, PersistenceError>
     where
         T: DeserializeOwned,
@@ -108,6 +115,16 @@ impl<'c> Log<'c> {
 
         let mut buf = Vec::new();
 
+        if source_size > 0 {
+            buf.resize(HEADER_SIZE, 0);
+            source
+                .read_exact(&mut buf)
+                .await
+                .map_err(|_| PersistenceError::UnsupportedFormat(None))?;
+            check_header(&buf)?;
+            buf.clear();
+        }
+
         while source
             .seek(SeekFrom::Current(0))
             .await
@@ -136,6 +153,8 @@ impl<'c> Log<'c> {
 
     /// Appends [make_log_entry] result of `source` to `destination`
     ///
+    /// Entry, that starts a new file, is prefixed with format header.
+    ///
     /// [make_log_entry]: Log::make_log_entry
     async fn append<P, S>(&self, source: &S, destination: P) -> Result<(), PersistenceError>
     where
@@ -152,15 +171,25 @@ impl<'c> Log<'c> {
             }
         }
 
-        OpenOptions::new()
+        let mut file = OpenOptions::new()
             .create(true)
             .append(true)
             .open(path)
             .await
-            .map_err(PersistenceError::from)?
-            .write_all(&Self::make_log_entry(source)?)
-            .await
-            .map_err(PersistenceError::from)
+            .map_err(PersistenceError::from)?;
+
+        let entry = Self::make_log_entry(source)?;
+
+        // Header and first entry are written at once, so file is never left without header
+        let buf = if file.metadata().await.map_err(PersistenceError::from)?.len() == 0 {
+            let mut buf = make_header().to_vec();
+            buf.extend(entry);
+            buf
+        } else {
+            entry
+        };
+
+        file.write_all(&buf).await.map_err(PersistenceError::from)
     }
 
     /// Get log entries from `source` log file using [parse_log]
@@ -216,9 +245,22 @@ impl<'c> Log<'c> {
             Err(e) => return Err(e),
         };
 
+        // Compacted deduplication index is stored separately, as it's not part of database
+        let mut deduplication = if self.config.compaction {
+            self.get_snapshot().load_deduplication(&source).await?
+        } else {
+            DeduplicationIndex::default()
+        };
+
+        deduplication.apply_log(&events);
+
         let database = if self.config.compaction {
             let compaction_path = source.as_ref().join(QUEUE_COMPACTION_FILE);
 
+            self.get_snapshot()
+                .persist(&deduplication, source.as_ref().join(DEDUPLICATION_FILE))
+                .await?;
+
             let inner_db = match self.get_snapshot().load::<DB, _>(&compaction_path).await {
                 Ok(mut database) => {
                     database.apply_log(events);
@@ -257,9 +299,9 @@ impl<'c> Log<'c> {
                     Err(e) => return Err(e)
                 };
 
-                let queue = Queue::new(database, replication_storage);
+                let queue = Queue::new(database, deduplication, replication_storage);
             } else {
-                let queue = Queue::new(database);
+                let queue = Queue::new(database, deduplication);
             }
         }
 
@@ -290,12 +332,13 @@ impl<'c> Log<'c> {
 mod tests {
     use std::{borrow::Cow, io::Cursor};
 
+    use chrono::Utc;
     use maybe_owned::MaybeOwned;
     use spartan_lib::core::{
         db::TreeDatabase,
         dispatcher::StatusAwareDispatcher,
         message::{builder::MessageBuilder, Message},
-        payload::Dispatchable,
+        payload::{Dispatchable, Identifiable},
     };
     use tempfile::{NamedTempFile, TempDir};
 
@@ -337,8 +380,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_serialize_log_entry() {
-        let entry = Log::make_log_entry(&vec![1u32, 2, 3]).unwrap();
-        let parsed = Log::parse_log::<Vec<u32>, _>(&mut Cursor::new(entry))
+        let mut entries = make_header().to_vec();
+        entries.append(&mut Log::make_log_entry(&vec![1u32, 2, 3]).unwrap());
+        let parsed = Log::parse_log::<Vec<u32>, _>(&mut Cursor::new(entries))
             .await
             .unwrap();
         assert_eq!(parsed.len(), 1);
@@ -347,7 +391,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_multiple_log_entries() {
-        let mut entries = Vec::new();
+        let mut entries = make_header().to_vec();
         entries.append(&mut Log::make_log_entry(&vec![1u32, 2, 3]).unwrap());
         entries.append(&mut Log::make_log_entry(&vec![4, 5, 6]).unwrap());
         entries.append(&mut Log::make_log_entry(&vec![7, 8, 9]).unwrap());
@@ -358,12 +402,23 @@ mod tests {
         assert_eq!(parsed, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]);
     }
 
+    #[tokio::test]
+    async fn test_headerless_log() {
+        let entries = Log::make_log_entry(&vec![1u32, 2, 3]).unwrap();
+
+        assert!(matches!(
+            Log::parse_log::<Vec<u32>, _>(&mut Cursor::new(entries)).await,
+            Err(PersistenceError::UnsupportedFormat(None))
+        ));
+    }
+
     #[tokio::test]
     async fn test_persist_and_restore_from_events() {
         let tempdir = TempDir::new().expect("Unable to create temporary test directory");
-        let event = Event::Push(MaybeOwned::Owned(
-            MessageBuilder::default().body("Hello").compose().unwrap(),
-        ));
+        let event = Event::Push(
+            MaybeOwned::Owned(MessageBuilder::default().body("Hello").compose().unwrap()),
+            None,
+        );
 
         let config = PersistenceConfig {
             mode: Persistence::Log,
@@ -377,15 +432,30 @@ mod tests {
 
         let queue: DB = log.load_queue("test").await.unwrap();
 
-        assert_eq!(queue.database().await.pop().unwrap().body(), "Hello");
+        assert_eq!(
+            queue
+                .database()
+                .await
+                .pop(Utc::now().timestamp())
+                .unwrap()
+                .body(),
+            "Hello"
+        );
     }
 
     #[tokio::test]
     async fn test_compaction() {
         let tempdir = TempDir::new().expect("Unable to create temporary test directory");
-        let event = Event::Push(MaybeOwned::Owned(
-            MessageBuilder::default().body("Hello").compose().unwrap(),
-        ));
+        let event = Event::Push(
+            MaybeOwned::Owned(
+                MessageBuilder::default()
+                    .body("Hello")
+                    .attribute("content-type", "text/plain")
+                    .compose()
+                    .unwrap(),
+            ),
+            None,
+        );
 
         let config = PersistenceConfig {
             mode: Persistence::Log,
@@ -399,7 +469,15 @@ mod tests {
 
         let queue: DB = log.load_queue("test").await.unwrap();
 
-        assert_eq!(queue.database().await.pop().unwrap().body(), "Hello");
+        assert_eq!(
+            queue
+                .database()
+                .await
+                .pop(Utc::now().timestamp())
+                .unwrap()
+                .body(),
+            "Hello"
+        );
 
         assert!(matches!(
             log.load::<Event, _>(Path::new("test").join(QUEUE_FILE))
@@ -414,6 +492,39 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(database.pop().unwrap().body(), "Hello");
+        let message = database.pop(Utc::now().timestamp()).unwrap();
+        assert_eq!(message.body(), "Hello");
+        assert_eq!(message.attributes().get("content-type"), Some("text/plain"));
+    }
+
+    #[tokio::test]
+    async fn test_restore_deduplication() {
+        let tempdir = TempDir::new().expect("Unable to create temporary test directory");
+        let message = MessageBuilder::default().body("Hello").compose().unwrap();
+
+        let config = PersistenceConfig {
+            mode: Persistence::Log,
+            path: Cow::Borrowed(tempdir.path()),
+            timer: 0,
+            compaction: true,
+        };
+        let log = Log::new(&config);
+
+        log.persist_event(
+            &Event::Push(
+                MaybeOwned::Borrowed(&message),
+                Some(("hello".into(), i64::MAX)),
+            ),
+            "test",
+        )
+        .await
+        .unwrap();
+
+        let queue: DB = log.load_queue("test").await.unwrap();
+        assert_eq!(queue.deduplication().await.get("hello"), Some(message.id()));
+
+        // Log is pruned by compaction, so index is restored from compacted file
+        let queue: DB = log.load_queue("test").await.unwrap();
+        assert_eq!(queue.deduplication().await.get("hello"), Some(message.id()));
     }
 }
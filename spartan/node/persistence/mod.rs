@@ -12,6 +12,7 @@ pub mod log;
 pub mod snapshot;
 
 use std::{
+    convert::TryInto,
     io::{Error as IoError, ErrorKind},
     num::TryFromIntError,
 };
@@ -21,11 +22,68 @@ use thiserror::Error;
 
 use crate::actions::RespondableError;
 
+/// Version of persisted files format
+///
+/// Bincode doesn't support adding fields to persisted structures,
+/// so version must be incremented on every change of [`Message`] or [`Event`] layout.
+/// Files of other versions are refused instead of being misread.
+///
+/// [`Message`]: spartan_lib::core::message::Message
+/// [`Event`]: crate::node::event::Event
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+/// Magic bytes, that start every persisted file
+const FORMAT_MAGIC: &[u8; 4] = b"SPTN";
+
+/// Size of persisted file header in bytes
+pub(crate) const HEADER_SIZE: usize = FORMAT_MAGIC.len() + 4;
+
+/// Make header of persisted file
+///
+/// ```
+/// +-----+-------+
+/// |Magic|Version|
+/// +-----+-------+
+/// ```
+pub(crate) fn make_header() -> [u8; HEADER_SIZE] {
+    let mut header = [0; HEADER_SIZE];
+    header[..FORMAT_MAGIC.len()].copy_from_slice(FORMAT_MAGIC);
+    header[FORMAT_MAGIC.len()..].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+    header
+}
+
+/// Check header of persisted file, returning the rest of file
+///
+/// Files without header were written by releases before format versioning was introduced.
+pub(crate) fn check_header(bytes: &[u8]) -> Result<&[u8], PersistenceError> {
+    if bytes.len() < HEADER_SIZE || &bytes[..FORMAT_MAGIC.len()] != FORMAT_MAGIC {
+        return Err(PersistenceError::UnsupportedFormat(None));
+    }
+
+    let version = u32::from_le_bytes(
+        bytes[FORMAT_MAGIC.len()..HEADER_SIZE]
+            .try_into()
+            .expect("Header has fixed size"),
+    );
+
+    if version == FORMAT_VERSION {
+        Ok(&bytes[HEADER_SIZE..])
+    } else {
+        Err(PersistenceError::UnsupportedFormat(Some(version)))
+    }
+}
+
 /// Errors, that may occur during persistence process
 #[derive(Error, Debug)]
 pub enum PersistenceError {
     #[error("File in database directory has invalid format: {0}")]
     InvalidFileFormat(BincodeError),
+    #[error(
+        "File in database directory has unsupported format version {0:?} (expected {}). \
+        Files of other Spartan releases can't be loaded, clear database directory before upgrading",
+        FORMAT_VERSION
+    )]
+    UnsupportedFormat(Option<u32>),
     #[error("Unable to serialize database: {0}")]
     SerializationError(BincodeError),
     #[error("Log entry size is too big for current platform")]
@@ -46,3 +104,32 @@ impl From<IoError> for PersistenceError {
 }
 
 impl RespondableError for PersistenceError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_header, make_header, PersistenceError, FORMAT_VERSION, HEADER_SIZE};
+
+    #[test]
+    fn test_header() {
+        let mut file = make_header().to_vec();
+        file.extend(&[1, 2, 3]);
+
+        assert_eq!(check_header(&file).unwrap(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_unsupported_header() {
+        assert!(matches!(
+            check_header(&[1, 2, 3]),
+            Err(PersistenceError::UnsupportedFormat(None))
+        ));
+
+        let mut file = make_header();
+        file[HEADER_SIZE - 4..].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+
+        assert!(matches!(
+            check_header(&file),
+            Err(PersistenceError::UnsupportedFormat(Some(version))) if version == FORMAT_VERSION + 1
+        ));
+    }
+}
@@ -1,17 +1,30 @@
-use std::path::Path;
+use std::{collections::BTreeSet, io::ErrorKind, path::Path};
 
 use bincode::{deserialize, serialize};
 use cfg_if::cfg_if;
 use serde::{de::DeserializeOwned, Serialize};
-use tokio::fs::{create_dir, read, write};
+use tokio::fs::{create_dir, read, remove_dir_all, write};
 
 use crate::{
     config::persistence::PersistenceConfig,
-    node::{persistence::PersistenceError, Queue},
+    node::{
+        deduplication::DeduplicationIndex,
+        persistence::{check_header, make_header, PersistenceError},
+        schedule::Schedules,
+        Queue,
+    },
 };
 
 const QUEUE_FILE: &str = "queue";
 
+pub(crate) const DEDUPLICATION_FILE: &str = "deduplication";
+
+/// Node schedules file, hidden to not clash with queue directories
+pub(crate) const SCHEDULES_FILE: &str = ".schedules";
+
+/// Node queues file, that contains queues created at runtime
+pub(crate) const QUEUES_FILE: &str = ".queues";
+
 #[cfg(feature = "replication")]
 pub(crate) const REPLICATION_FILE: &str = "replication";
 
@@ -35,7 +48,7 @@ impl<'c> Snapshot<'c> {
         Snapshot { config }
     }
 
-    /// Serialize `source` into `destination`.
+    /// Serialize `source` into `destination`, prefixed with format header.
     pub(crate) async fn persist<S, P>(
         &self,
         source: &S,
@@ -55,15 +68,15 @@ impl<'c> Snapshot<'c> {
             }
         }
 
-        write(
-            path,
-            serialize(source).map_err(PersistenceError::SerializationError)?,
-        )
-        .await
-        .map_err(PersistenceError::from)
+        let mut file = make_header().to_vec();
+        file.extend(serialize(source).map_err(PersistenceError::SerializationError)?);
+
+        write(path, file).await.map_err(PersistenceError::from)
     }
 
     /// Load serialized database from `source`
+    ///
+    /// File with missing or different format header is refused with [`PersistenceError::UnsupportedFormat`].
     pub(crate) async fn load<S, P>(&self, source: P) -> Result<S, PersistenceError>
     where
         P: AsRef<Path>,
@@ -73,8 +86,9 @@ impl<'c> Snapshot<'c> {
 
         debug!("Loading from {}", path.display());
 
-        deserialize(&read(path).await.map_err(PersistenceError::from)?)
-            .map_err(PersistenceError::InvalidFileFormat)
+        let file = read(path).await.map_err(PersistenceError::from)?;
+
+        deserialize(check_header(&file)?).map_err(PersistenceError::InvalidFileFormat)
     }
 
     /// Persist queue with provided [`PersistMode`]
@@ -82,6 +96,8 @@ impl<'c> Snapshot<'c> {
     /// Usually, when using this driver you may prefer [`PersistMode::Queue`],
     /// but if your driver doesn't support replication storage serialization,
     /// then pair it with [`Snapshot`] and choose [`PersistMode::Replication`] mode
+    ///
+    /// Queue, that was removed from node, is skipped
     pub async fn persist_queue<P, DB>(
         &self,
         name: P,
@@ -93,13 +109,25 @@ impl<'c> Snapshot<'c> {
         DB: Serialize,
     {
         if let PersistMode::Queue = mode {
-            self.persist(&*queue.database().await, name.as_ref().join(QUEUE_FILE))
-                .await?;
+            self.persist_file(
+                queue,
+                &*queue.database().await,
+                name.as_ref().join(QUEUE_FILE),
+            )
+            .await?;
+
+            self.persist_file(
+                queue,
+                &*queue.deduplication().await,
+                name.as_ref().join(DEDUPLICATION_FILE),
+            )
+            .await?;
         }
 
         #[cfg(feature = "replication")]
         {
-            self.persist(
+            self.persist_file(
+                queue,
                 &*queue.replication_storage().await,
                 name.as_ref().join(REPLICATION_FILE),
             )
@@ -109,6 +137,94 @@ impl<'c> Snapshot<'c> {
         Ok(())
     }
 
+    /// Persist file of queue, unless queue was removed from node
+    ///
+    /// Removal flag is locked last, after `source` is already locked,
+    /// so removal is never waiting for queue locks.
+    async fn persist_file<S, P, DB>(
+        &self,
+        queue: &Queue<DB>,
+        source: &S,
+        destination: P,
+    ) -> Result<(), PersistenceError>
+    where
+        S: Serialize,
+        P: AsRef<Path>,
+    {
+        let removed = queue.removed().await;
+
+        if *removed {
+            Ok(())
+        } else {
+            self.persist(source, destination).await
+        }
+    }
+
+    /// Load deduplication index of queue
+    ///
+    /// If there is no index file, then empty index is returned
+    pub(crate) async fn load_deduplication<P>(
+        &self,
+        name: P,
+    ) -> Result<DeduplicationIndex, PersistenceError>
+    where
+        P: AsRef<Path>,
+    {
+        match self.load(name.as_ref().join(DEDUPLICATION_FILE)).await {
+            Ok(deduplication) => Ok(deduplication),
+            Err(PersistenceError::FileOpenError(e)) => {
+                error!("{}", e);
+                Ok(DeduplicationIndex::default())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Load persisted node schedules
+    ///
+    /// If there is no schedules file, then empty set is returned
+    pub(crate) async fn load_schedules(&self) -> Result<Schedules, PersistenceError> {
+        match self.load(SCHEDULES_FILE).await {
+            Ok(schedules) => Ok(schedules),
+            Err(PersistenceError::FileOpenError(e)) => {
+                error!("{}", e);
+                Ok(Schedules::default())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Load names of queues, that were created at runtime
+    ///
+    /// If there is no queues file, then empty set is returned
+    pub(crate) async fn load_queues(&self) -> Result<BTreeSet<Box<str>>, PersistenceError> {
+        match self.load(QUEUES_FILE).await {
+            Ok(queues) => Ok(queues),
+            Err(PersistenceError::FileOpenError(e)) => {
+                error!("{}", e);
+                Ok(BTreeSet::new())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Remove all persisted files of queue
+    ///
+    /// Queue without persisted files is ignored
+    pub(crate) async fn remove_queue<P>(&self, name: P) -> Result<(), PersistenceError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = self.config.path.join(name);
+
+        debug!("Removing {}", path.display());
+
+        match remove_dir_all(path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(PersistenceError::from(e)),
+            _ => Ok(()),
+        }
+    }
+
     /// Deserialize queue from file
     pub async fn load_queue<P, DB>(&self, name: P) -> Result<Queue<DB>, PersistenceError>
     where
@@ -116,6 +232,7 @@ impl<'c> Snapshot<'c> {
         DB: DeserializeOwned,
     {
         let database = self.load(name.as_ref().join(QUEUE_FILE)).await?;
+        let deduplication = self.load_deduplication(&name).await?;
 
         cfg_if! {
             if #[cfg(feature = "replication")] {
@@ -128,9 +245,9 @@ impl<'c> Snapshot<'c> {
                     Err(e) => return Err(e)
                 };
 
-                let queue = Queue::new(database, replication_storage);
+                let queue = Queue::new(database, deduplication, replication_storage);
             } else {
-                let queue = Queue::new(database);
+                let queue = Queue::new(database, deduplication);
             }
         }
 
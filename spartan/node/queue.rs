@@ -1,13 +1,58 @@
-use tokio::sync::{Mutex, MutexGuard};
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use maybe_owned::MaybeOwned;
+use spartan_lib::core::{db::Database, message::Message, payload::Identifiable};
+use thiserror::Error;
+use tokio::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use warp::hyper::StatusCode;
 
 #[cfg(feature = "replication")]
 use crate::node::replication::storage::ReplicationStorage;
-use crate::node::{event::Event, persistence::PersistenceError, Manager};
+use crate::{
+    actions::RespondableError,
+    node::{
+        counters::{Counter, Counters},
+        deduplication::{DeduplicationIndex, DEFAULT_DEDUPLICATION_WINDOW},
+        event::Event,
+        persistence::PersistenceError,
+        Manager,
+    },
+};
+
+#[derive(Error, Debug)]
+pub enum PushError {
+    #[error("Message with the same ID already exists")]
+    MessageConflict,
+    #[error(transparent)]
+    PersistenceError(#[from] PersistenceError),
+}
+
+impl RespondableError for PushError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PushError::MessageConflict => StatusCode::CONFLICT,
+            PushError::PersistenceError(e) => e.status_code(),
+        }
+    }
+}
 
 pub struct Queue<DB> {
     /// Inner database
     database: Mutex<DB>,
 
+    /// Deduplication index of pushed messages
+    deduplication: Mutex<DeduplicationIndex>,
+
+    /// Operation counters since start
+    counters: Counters,
+
+    /// Set, when queue is removed from node
+    ///
+    /// Lock is held while persisted files of queue are written or removed,
+    /// so jobs and actions, that still hold removed queue, don't recreate its files.
+    removed: RwLock<bool>,
+
     #[cfg(feature = "replication")]
     /// Replication storage
     /// None if replication is not enabled
@@ -21,6 +66,9 @@ where
     fn default() -> Self {
         Queue {
             database: Mutex::new(DB::default()),
+            deduplication: Mutex::new(DeduplicationIndex::default()),
+            counters: Counters::default(),
+            removed: RwLock::new(false),
             #[cfg(feature = "replication")]
             replication_storage: Mutex::new(None),
         }
@@ -29,17 +77,27 @@ where
 
 impl<DB> Queue<DB> {
     #[cfg(feature = "replication")]
-    pub fn new(database: DB, replication_storage: Option<ReplicationStorage>) -> Queue<DB> {
+    pub fn new(
+        database: DB,
+        deduplication: DeduplicationIndex,
+        replication_storage: Option<ReplicationStorage>,
+    ) -> Queue<DB> {
         Queue {
             database: Mutex::new(database),
+            deduplication: Mutex::new(deduplication),
+            counters: Counters::default(),
+            removed: RwLock::new(false),
             replication_storage: Mutex::new(replication_storage),
         }
     }
 
     #[cfg(not(feature = "replication"))]
-    pub fn new(database: DB) -> Queue<DB> {
+    pub fn new(database: DB, deduplication: DeduplicationIndex) -> Queue<DB> {
         Queue {
             database: Mutex::new(database),
+            deduplication: Mutex::new(deduplication),
+            counters: Counters::default(),
+            removed: RwLock::new(false),
         }
     }
 
@@ -47,6 +105,31 @@ impl<DB> Queue<DB> {
         self.database.lock().await
     }
 
+    pub async fn deduplication(&self) -> MutexGuard<'_, DeduplicationIndex> {
+        self.deduplication.lock().await
+    }
+
+    pub fn counters(&self) -> &Counters {
+        &self.counters
+    }
+
+    /// Check, if queue was removed from node
+    ///
+    /// Guard has to be held while persisted files of queue are written,
+    /// and no other queue lock may be acquired while it's held.
+    pub async fn removed(&self) -> RwLockReadGuard<'_, bool> {
+        self.removed.read().await
+    }
+
+    /// Mark queue as removed from node
+    ///
+    /// Guard has to be held while persisted files of queue are removed.
+    pub async fn mark_removed(&self) -> RwLockWriteGuard<'_, bool> {
+        let mut removed = self.removed.write().await;
+        *removed = true;
+        removed
+    }
+
     #[cfg(feature = "replication")]
     pub async fn replication_storage(&self) -> MutexGuard<'_, Option<ReplicationStorage>> {
         self.replication_storage.lock().await
@@ -75,7 +158,16 @@ impl<DB> Queue<DB> {
         manager: &Manager<'_>,
         event: Event<'_>,
     ) -> Result<(), PersistenceError> {
-        manager.log(name, &event).await?;
+        {
+            let removed = self.removed().await;
+
+            if *removed {
+                debug!("Skipping event of removed queue \"{}\"", name);
+                return Ok(());
+            }
+
+            manager.log(name, &event).await?;
+        }
 
         #[cfg(feature = "replication")]
         if let Some(storage) = self.replication_storage().await.as_mut() {
@@ -86,6 +178,162 @@ impl<DB> Queue<DB> {
     }
 }
 
+impl<DB> Queue<DB>
+where
+    DB: Database<Message, PositionKey = <Message as Identifiable>::Id>,
+{
+    /// Push message to queue and log push event
+    ///
+    /// If `deduplication_id` is provided and message with the same deduplication ID
+    /// was pushed within queue deduplication window, then message is discarded.
+    ///
+    /// Message with ID, that already exists in queue, is rejected with [`PushError::MessageConflict`].
+    ///
+    /// Returns ID of pushed message, or ID of original message, if message was discarded.
+    pub async fn push(
+        &self,
+        name: &str,
+        manager: &Manager<'_>,
+        message: Message,
+        deduplication_id: Option<Box<str>>,
+    ) -> Result<<Message as Identifiable>::Id, PushError> {
+        let id = message.id();
+
+        // Index stays locked until message is pushed, which serializes pushes to queue,
+        // so concurrent duplicates are discarded and concurrent pushes with the same ID conflict.
+        // Database is locked only to check and insert message, so readers are not blocked while event is logged
+        let mut deduplication = self.deduplication().await;
+
+        if let Some(id) = deduplication_id
+            .as_ref()
+            .and_then(|deduplication_id| deduplication.get(deduplication_id))
+        {
+            return Ok(id);
+        }
+
+        if self.database().await.get(id).is_some() {
+            return Err(PushError::MessageConflict);
+        }
+
+        let entry = deduplication_id.map(|deduplication_id| {
+            let window = manager
+                .queue_config(name)
+                .and_then(|config| config.deduplication_window)
+                .unwrap_or(DEFAULT_DEDUPLICATION_WINDOW);
+
+            (deduplication_id, Utc::now().timestamp() + i64::from(window))
+        });
+
+        // Deduplication entry is logged along with message, so it's never restored without one
+        self.log_event(
+            name,
+            manager,
+            Event::Push(MaybeOwned::Borrowed(&message), entry.clone()),
+        )
+        .await?;
+
+        if let Some((deduplication_id, expires_at)) = entry {
+            deduplication.insert(deduplication_id, id, expires_at);
+        }
+
+        self.database().await.push_raw(message);
+        self.counters.add(Counter::Push, 1);
+
+        Ok(id)
+    }
+
+    /// Push batch of messages to queue and log it as single event
+    ///
+    /// Whole batch is checked before any message is pushed, so batch with conflicting ID is rejected completely.
+    /// Messages are discarded by deduplication ID like in [`Queue::push`],
+    /// including repeated deduplication IDs within the batch.
+    ///
+    /// Returns IDs of pushed messages, or IDs of original messages for discarded ones, in batch order.
+    pub async fn push_batch(
+        &self,
+        name: &str,
+        manager: &Manager<'_>,
+        messages: Vec<(Message, Option<Box<str>>)>,
+    ) -> Result<Vec<<Message as Identifiable>::Id>, PushError> {
+        // Index stays locked until batch is pushed, like in [`Queue::push`]
+        let mut deduplication = self.deduplication().await;
+        let database = self.database().await;
+
+        let window = manager
+            .queue_config(name)
+            .and_then(|config| config.deduplication_window)
+            .unwrap_or(DEFAULT_DEDUPLICATION_WINDOW);
+
+        let expires_at = Utc::now().timestamp() + i64::from(window);
+
+        let mut ids = Vec::with_capacity(messages.len());
+        let mut pushed = Vec::with_capacity(messages.len());
+        let mut pushed_ids = HashSet::new();
+        let mut entries = HashMap::new();
+
+        for (message, deduplication_id) in messages {
+            if let Some(deduplication_id) = &deduplication_id {
+                let original = deduplication
+                    .get(deduplication_id)
+                    .or_else(|| entries.get(deduplication_id).copied());
+
+                if let Some(id) = original {
+                    ids.push(id);
+                    continue;
+                }
+            }
+
+            let id = message.id();
+
+            if database.get(id).is_some() || !pushed_ids.insert(id) {
+                return Err(PushError::MessageConflict);
+            }
+
+            if let Some(deduplication_id) = deduplication_id {
+                entries.insert(deduplication_id, id);
+            }
+
+            ids.push(id);
+            pushed.push(message);
+        }
+
+        drop(database);
+
+        if pushed.is_empty() {
+            return Ok(ids);
+        }
+
+        let entries: Vec<_> = entries
+            .into_iter()
+            .map(|(deduplication_id, id)| (deduplication_id, id, expires_at))
+            .collect();
+
+        self.log_event(
+            name,
+            manager,
+            Event::PushBatch(
+                pushed.iter().map(MaybeOwned::Borrowed).collect(),
+                entries.clone(),
+            ),
+        )
+        .await?;
+
+        for (deduplication_id, id, expires_at) in entries {
+            deduplication.insert(deduplication_id, id, expires_at);
+        }
+
+        self.counters.add(Counter::Push, pushed.len());
+
+        let mut database = self.database().await;
+
+        for message in pushed {
+            database.push_raw(message);
+        }
+
+        Ok(ids)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::node::{
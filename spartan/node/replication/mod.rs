@@ -5,8 +5,8 @@
 //! |           |          |               |
 //! | Ping      +----><----+ Pong          |
 //! |           |          |               |
-//! | AskIndex  +----><----+ RecvIndex     |
-//! |           |          |               |
+//! | AskIndex  +----><--+-+ RecvIndex     |
+//! |           |        | |               |
 //! | SendRange +----><--+-+ RecvRange     |
 //! |           |        ^ |               |
 //! +-----------+        +-+ QueueNotFound |
@@ -32,6 +32,8 @@
 //!
 //! Replica sends back `RecvIndex` message, that contains data about available queues and their indexes.
 //!
+//! If one of replica queues has no replication storage, replica sends back `QueueNotFound` with its name instead.
+//!
 //! ## `SendRange` and `RecvRange` (and probably `QueueNotFound`)
 //!
 //! With `queue = index` array of each replica available, primary node takes new events from event log of each queue, and sends it to replica.
@@ -1,5 +1,6 @@
 use std::{
     borrow::Cow,
+    fmt::Display,
     hash::{Hash, Hasher},
 };
 
@@ -69,6 +70,27 @@ where
         self.batch.push(index);
     }
 
+    /// Record amount of events, that each replica is missing, in replication metrics
+    ///
+    /// `replicas` must be in the same order, as replicas were asked for indexes
+    pub async fn record_lag<R>(&self, manager: &Manager<'_>, replicas: &[R])
+    where
+        R: Display,
+    {
+        for (index, replica) in self.batch.iter().zip(replicas) {
+            let replica = replica.to_string();
+
+            for (name, start) in index.indexes.iter() {
+                if let Ok(queue) = manager.queue(name) {
+                    if let Some(storage) = queue.replication_storage().await.as_mut() {
+                        let lag = storage.get_primary().next_index().saturating_sub(*start);
+                        manager.metrics().set_replication_lag(&replica, name, lag);
+                    }
+                }
+            }
+        }
+    }
+
     pub async fn sync(mut self, manager: &Manager<'_>) -> PrimaryResult<Sync<'s, T>> {
         debug!("Starting event slice sync.");
 
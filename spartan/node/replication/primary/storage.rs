@@ -28,6 +28,11 @@ impl PrimaryStorage {
         self.next_index += 1;
     }
 
+    /// Get index of the next pushed event
+    pub fn next_index(&self) -> u64 {
+        self.next_index
+    }
+
     pub fn gc(&mut self) {
         let gc_threshold = self.gc_threshold;
 
@@ -73,7 +78,7 @@ mod tests {
         let mut storage = PrimaryStorage::default();
 
         for _ in 0..6 {
-            storage.push(Event::Pop);
+            storage.push(Event::Pop(0));
         }
 
         let slice = storage.slice(1).unwrap();
@@ -113,13 +118,13 @@ mod tests {
         let mut storage = PrimaryStorage::default();
 
         for _ in 0..6 {
-            storage.push(Event::Pop);
+            storage.push(Event::Pop(0));
         }
 
         let slice = storage.slice(1).unwrap();
         assert_eq!(slice.len(), 6);
 
         let (index, event) = slice.first().unwrap();
-        assert_eq!((**index, &**event), (1, &Event::Pop));
+        assert_eq!((**index, &**event), (1, &Event::Pop(0)));
     }
 }
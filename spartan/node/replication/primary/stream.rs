@@ -68,6 +68,10 @@ where
     async fn ask(&mut self) -> PrimaryResult<RecvIndex<'_, T>> {
         match self.exchange(PrimaryRequest::AskIndex).await? {
             ReplicaRequest::RecvIndex(recv) => Ok(RecvIndex::new(self, recv)),
+            ReplicaRequest::QueueNotFound(queue) => {
+                warn!("Queue {} is not replicated on replica", queue);
+                Err(PrimaryError::QueueConfigMismatch)
+            }
             _ => Err(PrimaryError::ProtocolMismatch),
         }
     }
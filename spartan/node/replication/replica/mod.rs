@@ -104,17 +104,17 @@ pub async fn accept_connection<'m, 'c>(
             let mut indexes = Vec::with_capacity(manager.config().queues.len());
 
             for (name, db) in manager.node().iter() {
-                let index = db
-                    .replication_storage()
-                    .await
-                    .as_mut()
-                    .expect("No database present")
-                    .get_replica()
-                    .get_index();
+                let index = match db.replication_storage().await.as_mut() {
+                    Some(storage) => storage.get_replica().get_index(),
+                    None => {
+                        error!("Queue {} has no replication storage", name);
+                        return ReplicaRequest::QueueNotFound(Cow::Owned(name.to_string()));
+                    }
+                };
 
                 debug!("Sending {} as confirmed index of {}", index, name);
 
-                indexes.push((Cow::Borrowed(name), index));
+                indexes.push((Cow::Owned(name.to_string()), index));
             }
 
             ReplicaRequest::RecvIndex(indexes.into_boxed_slice())
@@ -126,12 +126,16 @@ pub async fn accept_connection<'m, 'c>(
 
                 let index = range.last().map(|(index, _)| **index);
 
-                db.database()
-                    .await
-                    .apply_log(range.into_iter().map(|(_, event)| match event {
+                let events = range
+                    .into_iter()
+                    .map(|(_, event)| match event {
                         MaybeOwned::Owned(event) => event,
                         MaybeOwned::Borrowed(_) => unreachable!(),
-                    }));
+                    })
+                    .collect::<Vec<_>>();
+
+                db.deduplication().await.apply_log(&events);
+                db.database().await.apply_log(events);
 
                 if let Some(index) = index {
                     debug!("Setting {} as confirmed index of {}", index, queue);
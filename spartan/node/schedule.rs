@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+use warp::hyper::StatusCode;
+
+use crate::{
+    actions::RespondableError, config::schedule::ScheduleConfig, http::query::push::PushRequest,
+};
+
+#[derive(Error, Debug)]
+pub enum ScheduleError {
+    #[error("Invalid schedule")]
+    InvalidSchedule,
+    #[error("Schedule not found")]
+    ScheduleNotFound,
+}
+
+impl RespondableError for ScheduleError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ScheduleError::InvalidSchedule => StatusCode::BAD_REQUEST,
+            ScheduleError::ScheduleNotFound => StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+/// Recurring message schedule with its next push time
+#[derive(Serialize, Deserialize)]
+pub struct Schedule {
+    /// Schedule definition
+    pub config: ScheduleConfig,
+
+    /// Time of the next push
+    pub next_run: DateTime<Utc>,
+
+    /// Whether schedule is defined in server config, or created via API
+    pub from_config: bool,
+}
+
+impl Schedule {
+    /// Create new schedule, that runs next time after current moment
+    pub fn new(config: ScheduleConfig, from_config: bool) -> Result<Self, ScheduleError> {
+        let next_run = config
+            .next_run(Utc::now())
+            .ok_or(ScheduleError::InvalidSchedule)?;
+
+        Ok(Schedule {
+            config,
+            next_run,
+            from_config,
+        })
+    }
+}
+
+/// Due push of scheduled message
+pub struct ScheduledPush {
+    /// Name of the queue, that receives message
+    pub queue: Box<str>,
+
+    /// Deduplication ID, unique for each run of schedule
+    pub deduplication_id: Box<str>,
+
+    /// Pushed message
+    pub message: PushRequest,
+}
+
+/// Set of node schedules, identified by queue and schedule name
+#[derive(Serialize, Deserialize, Default)]
+pub struct Schedules {
+    schedules: BTreeMap<(Box<str>, Box<str>), Schedule>,
+}
+
+impl Schedules {
+    /// Create schedules from server config
+    ///
+    /// Invalid schedules are skipped
+    pub fn from_config(configs: &[ScheduleConfig]) -> Self {
+        let mut schedules = Schedules::default();
+
+        for config in configs {
+            match Schedule::new(config.clone(), true) {
+                Ok(schedule) => schedules.insert(schedule),
+                Err(e) => error!(
+                    "Schedule \"{}\" of \"{}\" skipped: {}",
+                    config.name, config.queue, e
+                ),
+            }
+        }
+
+        schedules
+    }
+
+    /// Insert schedule, replacing existing one with the same queue and name
+    pub fn insert(&mut self, schedule: Schedule) {
+        let key = (schedule.config.queue.clone(), schedule.config.name.clone());
+        self.schedules.insert(key, schedule);
+    }
+
+    /// Remove schedule of provided queue by its name
+    pub fn remove(&mut self, queue: &str, name: &str) -> Result<Schedule, ScheduleError> {
+        self.schedules
+            .remove(&(queue.into(), name.into()))
+            .ok_or(ScheduleError::ScheduleNotFound)
+    }
+
+    /// Iterate over schedules of provided queue
+    pub fn queue<'a>(&'a self, queue: &'a str) -> impl Iterator<Item = &'a Schedule> {
+        self.schedules
+            .values()
+            .filter(move |schedule| &*schedule.config.queue == queue)
+    }
+
+    /// Merge persisted schedules into current ones
+    ///
+    /// Schedules from server config keep their persisted next push time.
+    /// Schedules, that were created or replaced via API, take precedence over server config.
+    /// Persisted schedules, that were removed from server config, are dropped.
+    pub fn restore(&mut self, persisted: Schedules) {
+        for (key, schedule) in persisted.schedules {
+            match self.schedules.get_mut(&key) {
+                Some(current) if schedule.from_config => current.next_run = schedule.next_run,
+                _ if !schedule.from_config => {
+                    self.schedules.insert(key, schedule);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Take pushes of all schedules, that are due at provided time, and advance them
+    ///
+    /// Missed runs are not caught up, schedule is advanced to the first run after provided time.
+    ///
+    /// If scheduled message has ID, then ID of each run is derived from it and run time,
+    /// so runs don't conflict with each other.
+    pub fn take_due(&mut self, now: DateTime<Utc>) -> Vec<ScheduledPush> {
+        self.schedules
+            .values_mut()
+            .filter(|schedule| schedule.next_run <= now)
+            .map(|schedule| {
+                let run = schedule.next_run.timestamp().to_string();
+                let mut message = schedule.config.message.clone();
+
+                message.id = message.id.map(|id| Uuid::new_v5(&id, run.as_bytes()));
+
+                let push = ScheduledPush {
+                    queue: schedule.config.queue.clone(),
+                    deduplication_id: format!("schedule:{}:{}", schedule.config.name, run)
+                        .into_boxed_str(),
+                    message,
+                };
+
+                if let Some(next_run) = schedule.config.next_run(now) {
+                    schedule.next_run = next_run;
+                }
+
+                push
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    use super::{Schedule, Schedules};
+    use crate::config::schedule::ScheduleConfig;
+
+    fn config(name: &str, interval: u32) -> ScheduleConfig {
+        ScheduleConfig {
+            name: name.into(),
+            queue: "test".into(),
+            cron: None,
+            interval: Some(interval),
+            message: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_from_config() {
+        let schedules = Schedules::from_config(&[config("first", 60), config("second", 0)]);
+
+        assert_eq!(schedules.queue("test").count(), 1);
+        assert_eq!(schedules.queue("test_2").count(), 0);
+    }
+
+    #[test]
+    fn test_take_due() {
+        let mut schedules = Schedules::default();
+        schedules.insert(Schedule::new(config("first", 60), false).unwrap());
+        schedules.insert(Schedule::new(config("second", 600), false).unwrap());
+
+        assert!(schedules.take_due(Utc::now()).is_empty());
+
+        let now = Utc::now() + Duration::seconds(120);
+        let run = schedules.queue("test").next().unwrap().next_run;
+        let due = schedules.take_due(now);
+
+        assert_eq!(due.len(), 1);
+        assert_eq!(
+            &*due[0].deduplication_id,
+            &*format!("schedule:first:{}", run.timestamp())
+        );
+        assert_eq!(
+            schedules.queue("test").next().unwrap().next_run,
+            now + Duration::seconds(60)
+        );
+        assert!(schedules.take_due(now).is_empty());
+    }
+
+    #[test]
+    fn test_take_due_id() {
+        let mut first = config("first", 60);
+        first.message.id = Some(Uuid::new_v4());
+
+        let mut schedules = Schedules::default();
+        schedules.insert(Schedule::new(first, false).unwrap());
+
+        let now = Utc::now() + Duration::seconds(60);
+        let first_run = schedules.take_due(now).remove(0).message.id.unwrap();
+        let second_run = schedules
+            .take_due(now + Duration::seconds(60))
+            .remove(0)
+            .message
+            .id
+            .unwrap();
+
+        assert_ne!(first_run, second_run);
+    }
+
+    #[test]
+    fn test_restore() {
+        let mut persisted = Schedules::default();
+        let mut first = Schedule::new(config("first", 60), true).unwrap();
+        first.next_run = Utc::now() - Duration::seconds(30);
+        let run = first.next_run;
+        persisted.insert(first);
+        persisted.insert(Schedule::new(config("second", 60), true).unwrap());
+        persisted.insert(Schedule::new(config("third", 60), false).unwrap());
+        persisted.insert(Schedule::new(config("fourth", 30), false).unwrap());
+
+        let mut schedules = Schedules::from_config(&[config("first", 120), config("fourth", 120)]);
+        schedules.restore(persisted);
+
+        let names: Vec<_> = schedules
+            .queue("test")
+            .map(|schedule| &*schedule.config.name)
+            .collect();
+
+        assert_eq!(names, ["first", "fourth", "third"]);
+
+        let first = schedules.queue("test").next().unwrap();
+        assert_eq!(first.next_run, run);
+        assert_eq!(first.config.interval, Some(120));
+
+        // Schedule, that was replaced via API, keeps its changes
+        let fourth = schedules.queue("test").nth(1).unwrap();
+        assert_eq!(fourth.config.interval, Some(30));
+        assert!(!fourth.from_config);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut schedules = Schedules::from_config(&[config("first", 60)]);
+
+        assert!(schedules.remove("test", "second").is_err());
+        assert!(schedules.remove("test", "first").is_ok());
+        assert_eq!(schedules.queue("test").count(), 0);
+    }
+}
@@ -4,6 +4,12 @@ use crate::config::Config;
 
 pub static CONFIG: Lazy<Config> = Lazy::new(Config::default);
 
+/// Config without persistence, so queues created at runtime don't leave files behind
+pub static MEMORY_CONFIG: Lazy<Config> = Lazy::new(|| Config {
+    persistence: None,
+    ..Default::default()
+});
+
 #[macro_export]
 macro_rules! init_application_from_data {
     ($data:expr) => {
@@ -7,6 +7,66 @@ mod vec;
 pub use tree::TreeDatabase;
 pub use vec::VecDatabase;
 
+use crate::core::payload::{Dispatchable, Status};
+
+/// Amount of messages in each state at some moment
+///
+/// Each message is counted only once, with transit, expired and exhausted states checked first.
+/// Delays and TTLs are checked with second precision.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StatusCounts {
+    pub available: usize,
+    pub transit: usize,
+    pub delayed: usize,
+    pub exhausted: usize,
+    pub expired: usize,
+
+    /// UTC timestamp of the oldest message dispatch, [`None`] if database is empty
+    pub oldest: Option<i64>,
+}
+
+impl StatusCounts {
+    /// Count message in its state at provided UTC timestamp
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::db::StatusCounts;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    ///
+    /// let mut counts = StatusCounts::default();
+    /// let message = MessageBuilder::default().body("Hello, world").delay(600).compose().unwrap();
+    ///
+    /// counts.add(&message, Utc::now().timestamp());
+    ///
+    /// assert_eq!(counts.delayed, 1);
+    /// ```
+    pub fn add<M>(&mut self, message: &M, now: i64)
+    where
+        M: Status + Dispatchable,
+    {
+        let state = if message.requeueable() {
+            &mut self.transit
+        } else if matches!(message.expires_at(), Some(expires_at) if expires_at <= now) {
+            &mut self.expired
+        } else if !message.has_tries() {
+            &mut self.exhausted
+        } else if matches!(message.available_at(), Some(available_at) if available_at > now) {
+            &mut self.delayed
+        } else {
+            &mut self.available
+        };
+
+        *state += 1;
+
+        let dispatched_at = message.dispatched_at();
+
+        self.oldest = match self.oldest {
+            Some(oldest) if oldest <= dispatched_at => Some(oldest),
+            _ => Some(dispatched_at),
+        };
+    }
+}
+
 /// Interface for working with databases
 pub trait Database<M>: Default {
     type PositionKey: Copy;
@@ -65,7 +125,10 @@ pub trait Database<M>: Default {
 
     /// Get mutable message reference by database position key
     ///
+    /// Message is not re-indexed in `TreeDatabase`, so use [`StatusAwareDatabase`] methods to change its state
+    ///
     /// ```
+    /// use chrono::Utc;
     /// use spartan_lib::core::db::Database;
     /// use spartan_lib::core::db::VecDatabase;
     /// use spartan_lib::core::message::builder::MessageBuilder;
@@ -78,10 +141,31 @@ pub trait Database<M>: Default {
     ///
     /// let position = db.position(|msg| msg.obtainable()).unwrap();
     ///
-    /// db.get_mut(position).unwrap().reserve();
+    /// db.get_mut(position).unwrap().reserve(Utc::now().timestamp());
     /// ```
     fn get_mut(&mut self, position: Self::PositionKey) -> Option<&mut M>;
 
+    /// Iterate over database messages in insertion order, starting after provided cursor
+    ///
+    /// Each message is paired with its cursor, that can be passed back to continue iteration.
+    /// Cursor is an insertion id of message, so it stays valid after other messages are removed
+    ///
+    /// ```
+    /// use spartan_lib::core::db::Database;
+    /// use spartan_lib::core::db::VecDatabase;
+    ///
+    /// let mut db = VecDatabase::default();
+    ///
+    /// db.push_raw(1);
+    /// db.push_raw(2);
+    /// db.push_raw(3);
+    ///
+    /// let (cursor, _) = db.iter_after(None).next().unwrap();
+    ///
+    /// assert_eq!(db.iter_after(Some(cursor)).map(|(_, msg)| *msg).collect::<Vec<_>>(), vec![2, 3]);
+    /// ```
+    fn iter_after(&self, cursor: Option<u64>) -> Box<dyn Iterator<Item = (u64, &M)> + '_>;
+
     /// Delete message by database position key
     ///
     /// Returns owned message if position key is present in database
@@ -123,6 +207,27 @@ pub trait Database<M>: Default {
     where
         F: Fn(&M) -> bool;
 
+    /// Remove messages, that match predicate
+    ///
+    /// Returns owned messages, that were removed from database
+    ///
+    /// ```
+    /// use spartan_lib::core::db::Database;
+    /// use spartan_lib::core::db::VecDatabase;
+    ///
+    /// let mut db = VecDatabase::default();
+    ///
+    /// db.push_raw(1);
+    /// db.push_raw(2);
+    /// db.push_raw(3);
+    ///
+    /// assert_eq!(db.drain_filter(|msg| *msg != 2), vec![1, 3]);
+    /// assert_eq!(db.len(), 1);
+    /// ```
+    fn drain_filter<F>(&mut self, predicate: F) -> Vec<M>
+    where
+        F: Fn(&M) -> bool;
+
     /// Get database size
     ///
     /// ```
@@ -178,7 +283,11 @@ pub trait StatusAwareDatabase<M>: Database<M> {
     ///
     /// Removes message from tree in `TreeDatabase`, does nothing in `VecDatabase`
     ///
+    /// `update` is applied to message before `TreeDatabase` indexes it by reservation deadline,
+    /// so it must reserve message
+    ///
     /// ```
+    /// use chrono::Utc;
     /// use spartan_lib::core::db::{Database, StatusAwareDatabase};
     /// use spartan_lib::core::db::TreeDatabase;
     /// use spartan_lib::core::message::builder::MessageBuilder;
@@ -191,39 +300,222 @@ pub trait StatusAwareDatabase<M>: Database<M> {
     ///
     /// let position = db.position(|msg| msg.reservable()).unwrap();
     ///
-    /// // reserve returns mutable reference to message, so we can call reserve on message too.
-    /// let message = db.reserve(position).unwrap();
-    /// message.reserve();
+    /// // reserve applies update to message, so we can call reserve on message too.
+    /// let message = db.reserve(position, |msg| msg.reserve(Utc::now().timestamp())).unwrap();
+    ///
+    /// assert!(message.requeueable());
     /// ```
-    fn reserve(&mut self, position: Self::PositionKey) -> Option<&mut M>;
+    fn reserve<U>(&mut self, position: Self::PositionKey, update: U) -> Option<&mut M>
+    where
+        U: FnOnce(&mut M);
 
     /// Requeue message back to database
     ///
     /// Returns message back to tree in `TreeDatabase`, does nothing in `VecDatabase`
     ///
+    /// `update` is applied to message before it's returned to tree, so it may safely change message sort key
+    ///
     /// ```
+    /// use chrono::Utc;
     /// use spartan_lib::core::db::{Database, StatusAwareDatabase};
     /// use spartan_lib::core::db::TreeDatabase;
     /// use spartan_lib::core::message::builder::MessageBuilder;
     /// use spartan_lib::core::payload::{Dispatchable, Status, Identifiable};
     ///
     /// let mut db = TreeDatabase::default();
+    /// let now = Utc::now().timestamp();
     /// let message = MessageBuilder::default().body("Hello, world").compose().unwrap();
     ///
     /// db.push_raw(message);
     ///
     /// let position = db.position(|msg| msg.reservable()).unwrap();
     ///
-    /// let message = db.reserve(position).unwrap();
-    /// message.reserve();
+    /// let message = db.reserve(position, |msg| msg.reserve(now)).unwrap();
     ///
     /// let id = message.id();
     ///
     /// // requeue tries to find a message with provided id, and checks it for predicate
-    /// let message = db.requeue(id, |msg| msg.requeueable()).unwrap();
-    /// message.requeue();
+    /// db.requeue(id, |msg| msg.requeueable(), |msg| msg.requeue(now)).unwrap();
     /// ```
-    fn requeue<F>(&mut self, position: Self::RequeueKey, predicate: F) -> Option<&mut M>
+    fn requeue<F, U>(
+        &mut self,
+        position: Self::RequeueKey,
+        predicate: F,
+        update: U,
+    ) -> Option<&mut M>
     where
-        F: Fn(&M) -> bool;
+        F: Fn(&M) -> bool,
+        U: FnOnce(&mut M);
+
+    /// Update message, that is not reserved
+    ///
+    /// `update` is applied to message before `TreeDatabase` re-indexes it, so it may safely change message sort key
+    ///
+    /// ```
+    /// use spartan_lib::core::db::{Database, StatusAwareDatabase};
+    /// use chrono::Utc;
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::{Dispatchable, Status, Identifiable};
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    /// let id = message.id();
+    ///
+    /// db.push_raw(message);
+    ///
+    /// db.update(id, |msg| msg.reservable(), |msg| msg.postpone(Utc::now().timestamp() + 600)).unwrap();
+    ///
+    /// assert!(db.position(|msg| msg.obtainable()).is_none());
+    /// ```
+    fn update<F, U>(
+        &mut self,
+        position: Self::RequeueKey,
+        predicate: F,
+        update: U,
+    ) -> Option<&mut M>
+    where
+        F: Fn(&M) -> bool,
+        U: FnOnce(&mut M);
+
+    /// Update message, that is reserved
+    ///
+    /// Message is not returned to tree in `TreeDatabase`, so `update` must not change its sort key and state.
+    /// `update` may move reservation deadline, as `TreeDatabase` indexes new deadline after it's applied
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::db::{Database, StatusAwareDatabase};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::{Dispatchable, Status, Identifiable};
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let now = Utc::now().timestamp();
+    /// let message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    ///
+    /// db.push_raw(message);
+    ///
+    /// let position = db.position(|msg| msg.reservable()).unwrap();
+    ///
+    /// let message = db.reserve(position, |msg| msg.reserve(now)).unwrap();
+    ///
+    /// let id = message.id();
+    ///
+    /// let message = db.reserved(id, |msg| msg.requeueable(), |msg| msg.extend(None, now + 600)).unwrap();
+    ///
+    /// assert_eq!(message.reservation_deadline(), Some(now + 600));
+    /// ```
+    fn reserved<F, U>(
+        &mut self,
+        position: Self::RequeueKey,
+        predicate: F,
+        update: U,
+    ) -> Option<&mut M>
+    where
+        F: Fn(&M) -> bool,
+        U: FnOnce(&mut M);
+
+    /// Count messages in each state at provided UTC timestamp
+    ///
+    /// `TreeDatabase` keeps counts up to date on each change, and checks only messages with expired TTL,
+    /// `VecDatabase` checks all messages.
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::db::{Database, StatusAwareDatabase};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::{Dispatchable, Status};
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let now = Utc::now().timestamp();
+    ///
+    /// db.push_raw(MessageBuilder::default().body("Hello").compose().unwrap());
+    /// db.push_raw(MessageBuilder::default().body("world").delay(600).compose().unwrap());
+    ///
+    /// let position = db.position(|msg| msg.obtainable()).unwrap();
+    /// db.reserve(position, |msg| msg.reserve(now)).unwrap();
+    ///
+    /// let counts = db.status_counts(now);
+    ///
+    /// assert_eq!(counts.transit, 1);
+    /// assert_eq!(counts.delayed, 1);
+    /// assert_eq!(counts.available, 0);
+    /// ```
+    fn status_counts(&self, now: i64) -> StatusCounts;
+
+    /// Find reserved messages, whose reservation deadline is before `now`, without returning them to database
+    ///
+    /// `TreeDatabase` touches only due entries of its reservation deadline index, and drops entries of messages,
+    /// that were already returned. Returns position keys of found messages.
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::db::{Database, StatusAwareDatabase};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::{Dispatchable, Status};
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let message = MessageBuilder::default().body("Hello, world").max_tries(2).compose().unwrap();
+    ///
+    /// db.push_raw(message);
+    ///
+    /// let position = db.position(|msg| msg.reservable()).unwrap();
+    /// let deadline = db.reserve(position, |msg| msg.reserve(Utc::now().timestamp())).unwrap().reservation_deadline().unwrap();
+    ///
+    /// assert!(db.expired_reservations(deadline).is_empty());
+    /// assert_eq!(db.expired_reservations(deadline + 1), vec![position]);
+    /// assert!(db.get(position).unwrap().requeueable());
+    /// ```
+    fn expired_reservations(&mut self, now: i64) -> Vec<Self::RequeueKey>;
+
+    /// Find messages, whose TTL is over at `now`, and that could be reserved otherwise
+    ///
+    /// `TreeDatabase` touches only due entries of its expiration time index, `VecDatabase` checks all messages.
+    /// Returns position keys of found messages.
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::db::{Database, StatusAwareDatabase};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Identifiable;
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let message = MessageBuilder::default().body("Hello, world").ttl(600).compose().unwrap();
+    /// let now = Utc::now().timestamp();
+    ///
+    /// db.push_raw(message.clone());
+    ///
+    /// assert!(db.expired_messages(now).is_empty());
+    /// assert_eq!(db.expired_messages(now + 600), vec![message.id()]);
+    /// ```
+    fn expired_messages(&self, now: i64) -> Vec<Self::RequeueKey>;
+
+    /// Check if group of provided message already has reserved message
+    ///
+    /// Always `false` for messages without group
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::db::{Database, StatusAwareDatabase};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::{Dispatchable, Status};
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let message = MessageBuilder::default().body("Hello").group_id("order-1").compose().unwrap();
+    /// let next = MessageBuilder::default().body("world").group_id("order-1").compose().unwrap();
+    ///
+    /// db.push_raw(message);
+    ///
+    /// let position = db.position(|msg| msg.reservable()).unwrap();
+    ///
+    /// db.reserve(position, |msg| msg.reserve(Utc::now().timestamp())).unwrap();
+    ///
+    /// assert!(db.group_reserved(&next));
+    /// ```
+    fn group_reserved(&self, message: &M) -> bool;
 }
@@ -1,17 +1,172 @@
 use std::{
     collections::{hash_map::RandomState, BTreeMap, HashMap},
     hash::Hash,
+    ops::Bound,
 };
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::core::{
-    db::{Database, StatusAwareDatabase},
-    payload::{Identifiable, Sortable, Status},
+    db::{Database, StatusAwareDatabase, StatusCounts},
+    payload::{Dispatchable, Groupable, Identifiable, Sortable, Status},
 };
 
 type MessageStore<M, S = RandomState> = HashMap<<M as Identifiable>::Id, (u64, M), S>;
 type Tree<M> = BTreeMap<(<M as Sortable>::Sort, u64), <M as Identifiable>::Id>;
+type GroupStore<M, S = RandomState> = HashMap<<M as Groupable>::Group, GroupIndex<M>, S>;
+type TimeoutIndex<M> = BTreeMap<(i64, u64), <M as Identifiable>::Id>;
+type InsertionIndex<M> = BTreeMap<u64, <M as Identifiable>::Id>;
+type ExpirationIndex<M> = BTreeMap<(i64, u64), <M as Identifiable>::Id>;
+
+/// Index of single message group
+///
+/// Only first message of group is present in main index,
+/// and only if there are no reserved messages in group.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "<M as Identifiable>::Id: Serialize + DeserializeOwned, \
+                 <M as Sortable>::Sort: Serialize + DeserializeOwned")]
+struct GroupIndex<M>
+where
+    M: Identifiable + Sortable,
+{
+    /// Messages of group, that are waiting to be dispatched
+    queue: Tree<M>,
+
+    /// Reserved messages of group
+    reserved: Vec<<M as Identifiable>::Id>,
+}
+
+impl<M> Default for GroupIndex<M>
+where
+    M: Identifiable + Sortable,
+{
+    fn default() -> Self {
+        GroupIndex {
+            queue: BTreeMap::new(),
+            reserved: Vec::new(),
+        }
+    }
+}
+
+impl<M> GroupIndex<M>
+where
+    M: Identifiable + Sortable,
+    <M as Identifiable>::Id: Hash,
+{
+    /// Remove first message of group from main index
+    fn unlink(&self, queue_tree: &mut Tree<M>) {
+        if self.reserved.is_empty() {
+            if let Some((key, _)) = self.queue.iter().next() {
+                queue_tree.remove(key);
+            }
+        }
+    }
+
+    /// Return first message of group to main index, if group has no reserved messages
+    fn link(&self, queue_tree: &mut Tree<M>, objects: &MessageStore<M>) {
+        if self.reserved.is_empty() {
+            if let Some(((_, id), key)) = self.queue.iter().next() {
+                let message = &objects.get(key).unwrap().1;
+                queue_tree.insert((message.sort(), *id), *key);
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queue.is_empty() && self.reserved.is_empty()
+    }
+}
+
+/// Message state counts, that are kept up to date on each change
+///
+/// States, that change over time, are resolved at the moment of counting:
+/// only messages, whose TTL is already over, are checked one by one.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "<M as Identifiable>::Id: Serialize + DeserializeOwned")]
+struct StateIndex<M>
+where
+    M: Identifiable,
+{
+    /// Amount of reserved messages
+    reserved: usize,
+
+    /// Amount of messages without tries, that are not reserved
+    exhausted: usize,
+
+    /// Messages with TTL, that are not reserved, indexed by their expiration time
+    expirations: ExpirationIndex<M>,
+
+    /// Amount of delayed messages with tries, that are not reserved, per availability time
+    delays: BTreeMap<i64, usize>,
+
+    /// Amount of messages per dispatch time
+    dispatches: BTreeMap<i64, usize>,
+}
+
+impl<M> Default for StateIndex<M>
+where
+    M: Identifiable,
+{
+    fn default() -> Self {
+        StateIndex {
+            reserved: 0,
+            exhausted: 0,
+            expirations: BTreeMap::new(),
+            delays: BTreeMap::new(),
+            dispatches: BTreeMap::new(),
+        }
+    }
+}
+
+impl<M> StateIndex<M>
+where
+    M: Identifiable + Status + Dispatchable,
+{
+    /// Add message to counts, or remove it from them
+    fn track(&mut self, id: u64, message: &M, add: bool) {
+        fn adjust(counter: &mut usize, add: bool) {
+            if add {
+                *counter += 1;
+            } else {
+                *counter -= 1;
+            }
+        }
+
+        fn adjust_entry(counts: &mut BTreeMap<i64, usize>, key: i64, add: bool) {
+            let count = counts.entry(key).or_default();
+            adjust(count, add);
+
+            if *count == 0 {
+                counts.remove(&key);
+            }
+        }
+
+        adjust_entry(&mut self.dispatches, message.dispatched_at(), add);
+
+        if message.requeueable() {
+            adjust(&mut self.reserved, add);
+            return;
+        }
+
+        if let Some(expires_at) = message.expires_at() {
+            if add {
+                self.expirations.insert((expires_at, id), message.id());
+            } else {
+                self.expirations.remove(&(expires_at, id));
+            }
+        }
+
+        if !message.has_tries() {
+            adjust(&mut self.exhausted, add);
+        } else if let Some(available_at) = message.available_at() {
+            adjust_entry(&mut self.delays, available_at, add);
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = StateIndex::default();
+    }
+}
 
 /// Tree-based database
 ///
@@ -21,14 +176,24 @@ type Tree<M> = BTreeMap<(<M as Sortable>::Sort, u64), <M as Identifiable>::Id>;
 /// thus improving performance in comparison with [VecDatabase]
 ///
 /// [`TreeDatabase`] heavily relies on correct `M` implementation of Sortable
-/// as only first element of index is used to check if there are any available messages in queue.
+/// as only first element of each index partition is used to check if there are any available messages in queue.
+///
+/// Grouped messages are kept in separate per-group indexes, so only first message of each group,
+/// that has no reserved messages, is present in main index.
+///
+/// Reserved messages are indexed by their reservation deadline, so timed out reservations
+/// are found without scanning the whole storage.
+///
+/// All messages are also indexed by their insertion id, which provides stable iteration order.
+///
+/// Amounts of messages in each state are kept up to date, so they are counted without scanning the whole storage.
 ///
 /// [VecDatabase]: super::VecDatabase
 #[derive(Serialize, Deserialize)]
 #[serde(bound = "M: Serialize + DeserializeOwned")]
 pub struct TreeDatabase<M>
 where
-    M: Identifiable + Sortable,
+    M: Identifiable + Sortable + Groupable,
     <M as Identifiable>::Id: Hash,
 {
     last_insert_id: u64,
@@ -36,11 +201,21 @@ where
     objects: MessageStore<M>,
     #[serde(bound = "<M as Sortable>::Sort: Serialize + DeserializeOwned")]
     queue_tree: Tree<M>,
+    #[serde(bound = "<M as Groupable>::Group: Serialize + DeserializeOwned, \
+                     <M as Identifiable>::Id: Serialize + DeserializeOwned, \
+                     <M as Sortable>::Sort: Serialize + DeserializeOwned")]
+    groups: GroupStore<M>,
+    #[serde(bound = "<M as Identifiable>::Id: Serialize + DeserializeOwned")]
+    timeouts: TimeoutIndex<M>,
+    #[serde(bound = "<M as Identifiable>::Id: Serialize + DeserializeOwned")]
+    insertions: InsertionIndex<M>,
+    #[serde(bound = "<M as Identifiable>::Id: Serialize + DeserializeOwned")]
+    states: StateIndex<M>,
 }
 
 impl<M> Default for TreeDatabase<M>
 where
-    M: Identifiable + Sortable,
+    M: Identifiable + Sortable + Groupable,
     <M as Identifiable>::Id: Hash,
 {
     fn default() -> Self {
@@ -48,35 +223,98 @@ where
             last_insert_id: 0,
             objects: HashMap::new(),
             queue_tree: BTreeMap::new(),
+            groups: HashMap::new(),
+            timeouts: BTreeMap::new(),
+            insertions: BTreeMap::new(),
+            states: StateIndex::default(),
+        }
+    }
+}
+
+impl<M> TreeDatabase<M>
+where
+    M: Identifiable + Sortable + Groupable,
+    <M as Identifiable>::Id: Hash,
+{
+    /// Apply `update` to group index, keeping first message of group in main index up to date
+    fn update_group<U>(&mut self, group: &<M as Groupable>::Group, update: U)
+    where
+        U: FnOnce(&mut GroupIndex<M>),
+    {
+        if let Some(index) = self.groups.get_mut(group) {
+            index.unlink(&mut self.queue_tree);
+            update(index);
+            index.link(&mut self.queue_tree, &self.objects);
+
+            if index.is_empty() {
+                self.groups.remove(group);
+            }
         }
     }
 }
 
 impl<M> Database<M> for TreeDatabase<M>
 where
-    M: Identifiable + Sortable,
+    M: Identifiable + Sortable + Groupable + Status + Dispatchable,
     <M as Identifiable>::Id: Hash,
 {
     type PositionKey = <M as Identifiable>::Id;
 
     fn push_raw(&mut self, message: M) {
+        let key = message.id();
+
+        // Message with the same ID is replaced, so its index entries must not outlive it
+        self.delete_pos(key);
+
         let id = self.last_insert_id;
         self.last_insert_id += 1;
 
-        self.queue_tree.insert((message.sort(), id), message.id());
-        self.objects.insert(message.id(), (id, message));
+        let sort = message.sort();
+        let group = message.group().cloned();
+
+        self.states.track(id, &message, true);
+
+        if let Some(deadline) = message.reservation_deadline() {
+            self.timeouts.insert((deadline, id), key);
+        }
+
+        self.objects.insert(key, (id, message));
+        self.insertions.insert(id, key);
+
+        match group {
+            Some(group) => {
+                self.groups.entry(group.clone()).or_default();
+                self.update_group(&group, |index| {
+                    index.queue.insert((sort, id), key);
+                });
+            }
+            None => {
+                self.queue_tree.insert((sort, id), key);
+            }
+        }
     }
 
     fn position<F>(&self, predicate: F) -> Option<Self::PositionKey>
     where
         F: Fn(&M) -> bool,
     {
-        self.queue_tree
-            .values()
-            .next()
-            .map(|key| &self.objects.get(key).unwrap().1)
-            .filter(|message| predicate(message))
-            .map(|message| message.id())
+        let mut head = self.queue_tree.iter().next();
+
+        // Only first message of each partition is checked
+        while let Some(((sort, _), key)) = head {
+            let message = &self.objects.get(key).unwrap().1;
+
+            if predicate(message) {
+                return Some(message.id());
+            }
+
+            head = self
+                .queue_tree
+                .range((M::next_partition(sort)?, 0)..)
+                .next();
+        }
+
+        None
     }
 
     fn get(&self, position: Self::PositionKey) -> Option<&M> {
@@ -89,9 +327,36 @@ where
             .map(|message| &mut message.1)
     }
 
+    fn iter_after(&self, cursor: Option<u64>) -> Box<dyn Iterator<Item = (u64, &M)> + '_> {
+        let start = match cursor {
+            Some(cursor) => Bound::Excluded(cursor),
+            None => Bound::Unbounded,
+        };
+
+        Box::new(
+            self.insertions
+                .range((start, Bound::Unbounded))
+                .filter_map(move |(id, key)| Some((*id, &self.objects.get(key)?.1))),
+        )
+    }
+
     fn delete_pos(&mut self, position: Self::PositionKey) -> Option<M> {
         let (id, message) = self.objects.remove(&position)?;
-        self.queue_tree.remove(&(message.sort(), id));
+        let key = (message.sort(), id);
+
+        self.insertions.remove(&id);
+        self.states.track(id, &message, false);
+
+        match message.group() {
+            Some(group) => self.update_group(group, |index| {
+                index.queue.remove(&key);
+                index.reserved.retain(|reserved| *reserved != position);
+            }),
+            None => {
+                self.queue_tree.remove(&key);
+            }
+        }
+
         Some(message)
     }
 
@@ -99,17 +364,23 @@ where
     where
         F: Fn(&M) -> bool,
     {
-        let tree = &mut self.queue_tree;
-
-        self.objects.retain(|_, (id, message)| {
-            let preserve = predicate(message);
+        self.drain_filter(|message| !predicate(message));
+    }
 
-            if !preserve {
-                tree.remove(&(message.sort(), *id));
-            }
+    fn drain_filter<F>(&mut self, predicate: F) -> Vec<M>
+    where
+        F: Fn(&M) -> bool,
+    {
+        let keys = self
+            .objects
+            .iter()
+            .filter(|(_, (_, message))| predicate(message))
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
 
-            preserve
-        });
+        keys.into_iter()
+            .filter_map(|key| self.delete_pos(key))
+            .collect()
     }
 
     fn len(&self) -> usize {
@@ -124,66 +395,277 @@ where
         self.objects.clear();
         self.objects.shrink_to_fit();
         self.queue_tree.clear();
+        self.groups.clear();
+        self.groups.shrink_to_fit();
+        self.timeouts.clear();
+        self.insertions.clear();
+        self.states.clear();
     }
 }
 
 impl<M> StatusAwareDatabase<M> for TreeDatabase<M>
 where
-    M: Identifiable + Sortable + Status,
+    M: Identifiable + Sortable + Groupable + Status + Dispatchable,
     <M as Identifiable>::Id: Hash,
 {
     type RequeueKey = <M as Identifiable>::Id;
 
-    fn reserve(&mut self, position: Self::PositionKey) -> Option<&mut M> {
-        let objects = &mut self.objects;
-        let queue_tree = &mut self.queue_tree;
+    fn reserve<U>(&mut self, position: Self::PositionKey, update: U) -> Option<&mut M>
+    where
+        U: FnOnce(&mut M),
+    {
+        let (id, message) = self.objects.get_mut(&position)?;
+        let key = (message.sort(), *id);
+
+        self.states.track(*id, message, false);
+        update(message);
+        self.states.track(*id, message, true);
+
+        if let Some(deadline) = message.reservation_deadline() {
+            self.timeouts.insert((deadline, *id), position);
+        }
+
+        match message.group().cloned() {
+            Some(group) => self.update_group(&group, |index| {
+                index.queue.remove(&key);
+
+                if !index.reserved.contains(&position) {
+                    index.reserved.push(position);
+                }
+            }),
+            None => {
+                self.queue_tree.remove(&key);
+            }
+        }
+
+        self.get_mut(position)
+    }
 
-        objects
+    fn requeue<F, U>(
+        &mut self,
+        position: Self::RequeueKey,
+        predicate: F,
+        update: U,
+    ) -> Option<&mut M>
+    where
+        F: Fn(&M) -> bool,
+        U: FnOnce(&mut M),
+    {
+        let (id, message) = self
+            .objects
             .get_mut(&position)
-            .map(|message| {
-                queue_tree.remove(&(message.1.sort(), message.0));
-                message
-            })
-            .map(|message| &mut message.1)
+            .filter(|message| predicate(&message.1))?;
+
+        self.states.track(*id, message, false);
+        update(message);
+        self.states.track(*id, message, true);
+
+        let key = (message.sort(), *id);
+
+        // Check if message can be reserved later
+        // Without this check, requeue of message where tries == max_tries can lead to
+        // broken index, as it will be stuck until GC collects it
+        let has_tries = message.has_tries();
+
+        match message.group().cloned() {
+            Some(group) => self.update_group(&group, |index| {
+                index.reserved.retain(|reserved| *reserved != position);
+
+                if has_tries {
+                    index.queue.insert(key, position);
+                }
+            }),
+            None => {
+                if has_tries {
+                    self.queue_tree.insert(key, position);
+                }
+            }
+        }
+
+        self.get_mut(position)
     }
 
-    fn requeue<F>(&mut self, position: Self::RequeueKey, predicate: F) -> Option<&mut M>
+    fn update<F, U>(
+        &mut self,
+        position: Self::RequeueKey,
+        predicate: F,
+        update: U,
+    ) -> Option<&mut M>
     where
         F: Fn(&M) -> bool,
+        U: FnOnce(&mut M),
     {
-        let objects = &mut self.objects;
-        let queue_tree = &mut self.queue_tree;
+        let (id, message) = self
+            .objects
+            .get_mut(&position)
+            .filter(|message| !message.1.requeueable() && predicate(&message.1))?;
+
+        let old_key = (message.sort(), *id);
+
+        self.states.track(*id, message, false);
+        update(message);
+        self.states.track(*id, message, true);
+
+        let key = (message.sort(), *id);
 
-        objects
+        // Same as requeue, message without tries is not returned to index
+        let has_tries = message.has_tries();
+
+        match message.group().cloned() {
+            Some(group) => self.update_group(&group, |index| {
+                index.queue.remove(&old_key);
+
+                if has_tries {
+                    index.queue.insert(key, position);
+                }
+            }),
+            None => {
+                self.queue_tree.remove(&old_key);
+
+                if has_tries {
+                    self.queue_tree.insert(key, position);
+                }
+            }
+        }
+
+        self.get_mut(position)
+    }
+
+    fn reserved<F, U>(
+        &mut self,
+        position: Self::RequeueKey,
+        predicate: F,
+        update: U,
+    ) -> Option<&mut M>
+    where
+        F: Fn(&M) -> bool,
+        U: FnOnce(&mut M),
+    {
+        let (id, message) = self
+            .objects
             .get_mut(&position)
-            .filter(|message| predicate(&message.1))
-            .map(|message| {
-                // Check if message can be reserved later
-                // Without this check, requeue of message where tries == max_tries can lead to
-                // broken index, as it will be stuck until GC collects it
-                if message.1.has_tries() {
-                    queue_tree.insert((message.1.sort(), message.0), position);
+            .filter(|message| predicate(&message.1))?;
+
+        update(message);
+
+        // Entry of previous deadline is kept, and is dropped once it's due
+        if let Some(deadline) = message.reservation_deadline() {
+            self.timeouts.insert((deadline, *id), position);
+        }
+
+        self.get_mut(position)
+    }
+
+    fn status_counts(&self, now: i64) -> StatusCounts {
+        let states = &self.states;
+
+        let mut counts = StatusCounts {
+            transit: states.reserved,
+            exhausted: states.exhausted,
+            delayed: states
+                .delays
+                .range((Bound::Excluded(now), Bound::Unbounded))
+                .map(|(_, count)| count)
+                .sum(),
+            oldest: states.dispatches.keys().next().copied(),
+            ..Default::default()
+        };
+
+        // Expired messages are counted only as expired
+        for key in states
+            .expirations
+            .range(..=(now, u64::MAX))
+            .map(|(_, key)| key)
+        {
+            let message = &self.objects.get(key).unwrap().1;
+
+            counts.expired += 1;
+
+            if !message.has_tries() {
+                counts.exhausted -= 1;
+            } else if matches!(message.available_at(), Some(available_at) if available_at > now) {
+                counts.delayed -= 1;
+            }
+        }
+
+        counts.available = self.objects.len()
+            - counts.transit
+            - counts.expired
+            - counts.exhausted
+            - counts.delayed;
+
+        counts
+    }
+
+    fn expired_reservations(&mut self, now: i64) -> Vec<Self::RequeueKey> {
+        let due: Vec<_> = self
+            .timeouts
+            .iter()
+            .take_while(|((deadline, _), _)| *deadline < now)
+            .map(|(&key, &position)| (key, position))
+            .collect();
+
+        let mut expired = Vec::new();
+
+        // Entry of expired message is kept, so it's dropped by later calls once message is returned
+        for ((deadline, id), position) in due {
+            self.timeouts.remove(&(deadline, id));
+
+            let current = match self.objects.get(&position) {
+                Some((message_id, message)) if *message_id == id => message.reservation_deadline(),
+                _ => None,
+            };
+
+            if let Some(current) = current {
+                self.timeouts.insert((current, id), position);
+
+                if current < now && !expired.contains(&position) {
+                    expired.push(position);
                 }
+            }
+        }
 
-                message
-            })
-            .map(|message| &mut message.1)
+        expired
+    }
+
+    fn expired_messages(&self, now: i64) -> Vec<Self::RequeueKey> {
+        // Index contains all messages, that are not reserved, so exhausted ones are skipped
+        self.states
+            .expirations
+            .range(..=(now, u64::MAX))
+            .map(|(_, key)| key)
+            .filter(|key| self.objects.get(key).unwrap().1.has_tries())
+            .copied()
+            .collect()
+    }
+
+    fn group_reserved(&self, message: &M) -> bool {
+        matches!(
+            message.group().and_then(|group| self.groups.get(group)),
+            Some(index) if !index.reserved.is_empty()
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::Utc;
+
     use super::TreeDatabase;
     use crate::core::{
-        db::Database,
+        db::{Database, StatusAwareDatabase, StatusCounts},
         message::{builder::MessageBuilder, Message},
-        payload::{Identifiable, Status},
+        payload::{Dispatchable, Identifiable, Status},
     };
 
     fn create_database() -> TreeDatabase<Message> {
         TreeDatabase::default()
     }
 
+    fn now() -> i64 {
+        Utc::now().timestamp()
+    }
+
     macro_rules! create_message {
         () => {
             MessageBuilder::default()
@@ -217,6 +699,24 @@ mod tests {
         assert_eq!(database.queue_tree.len(), 1);
     }
 
+    #[test]
+    fn test_push_replace() {
+        let mut database = create_database();
+        let message = create_message!();
+        database.push_raw(message.clone());
+        database
+            .reserve(message.id(), |message| message.reserve(now()))
+            .unwrap();
+        database.push_raw(message.clone());
+        assert_eq!(database.objects.len(), 1);
+        assert_eq!(database.queue_tree.len(), 1);
+        assert_eq!(database.insertions.len(), 1);
+
+        database.delete_pos(message.id()).unwrap();
+        assert!(database.position(|_| true).is_none());
+        assert!(database.iter_after(None).next().is_none());
+    }
+
     #[test]
     fn test_position_get() {
         let mut database = create_database();
@@ -235,6 +735,241 @@ mod tests {
         position!(database, message3);
     }
 
+    #[test]
+    fn test_position_priority() {
+        let mut database = create_database();
+        let message1 = create_message!();
+        let message2 = MessageBuilder::default()
+            .body("Hello world")
+            .priority(5)
+            .compose()
+            .unwrap();
+        let message3 = MessageBuilder::default()
+            .body("Hello world")
+            .priority(10)
+            .delay(600)
+            .compose()
+            .unwrap();
+        database.push_raw(message1.clone());
+        database.push_raw(message2.clone());
+        database.push_raw(message3.clone());
+
+        let pos = database.position(|message| message.obtainable()).unwrap();
+        assert_eq!(pos, message2.id());
+        database.delete_pos(pos).unwrap();
+
+        let pos = database.position(|message| message.obtainable()).unwrap();
+        assert_eq!(pos, message1.id());
+        database.delete_pos(pos).unwrap();
+
+        assert!(database.position(|message| message.obtainable()).is_none());
+        assert_eq!(database.position(|_| true).unwrap(), message3.id());
+    }
+
+    #[test]
+    fn test_group_index() {
+        let mut database = create_database();
+        let grouped = || {
+            MessageBuilder::default()
+                .body("Hello world")
+                .group_id("group")
+                .compose()
+                .unwrap()
+        };
+        let message1 = grouped();
+        let message2 = grouped();
+        database.push_raw(message1.clone());
+        database.push_raw(message2.clone());
+        assert_eq!(database.queue_tree.len(), 1);
+
+        let pos = database.position(|_| true).unwrap();
+        assert_eq!(pos, message1.id());
+        database
+            .reserve(pos, |message| message.reserve(now()))
+            .unwrap();
+        assert!(database.queue_tree.is_empty());
+        assert!(database.group_reserved(&message2));
+
+        database.delete_pos(pos).unwrap();
+        assert_eq!(database.position(|_| true).unwrap(), message2.id());
+        assert!(!database.group_reserved(&message2));
+
+        database.retain(|_| false);
+        assert!(database.groups.is_empty());
+        assert!(database.queue_tree.is_empty());
+    }
+
+    #[test]
+    fn test_timeout_index() {
+        let mut database = create_database();
+        let message1 = create_message!();
+        let message2 = create_message!();
+        database.push_raw(message1.clone());
+        database.push_raw(message2.clone());
+
+        let deadline = database
+            .reserve(message1.id(), |message| message.reserve(now()))
+            .unwrap()
+            .reservation_deadline()
+            .unwrap();
+        database
+            .reserve(message2.id(), |message| message.reserve(now()))
+            .unwrap();
+        assert_eq!(database.timeouts.len(), 2);
+
+        database.delete_pos(message2.id()).unwrap();
+        assert!(database.expired_reservations(deadline).is_empty());
+        assert_eq!(database.timeouts.len(), 2);
+
+        assert_eq!(database.expired_reservations(i64::MAX), vec![message1.id()]);
+        assert_eq!(database.timeouts.len(), 1);
+        assert!(database.get(message1.id()).unwrap().requeueable());
+
+        // Shortened deadline is indexed, so reservation times out before previous deadline
+        database
+            .reserved(
+                message1.id(),
+                |message| message.requeueable(),
+                |message| message.extend(None, deadline - 10),
+            )
+            .unwrap();
+        assert_eq!(database.timeouts.len(), 2);
+        assert_eq!(
+            database.expired_reservations(deadline - 9),
+            vec![message1.id()]
+        );
+
+        database
+            .requeue(message1.id(), |_| true, |message| message.requeue(now()))
+            .unwrap();
+        assert!(database.expired_reservations(i64::MAX).is_empty());
+        assert!(database.timeouts.is_empty());
+    }
+
+    #[test]
+    fn test_status_counts() {
+        let mut database = create_database();
+        let now = now();
+
+        let counted = |database: &TreeDatabase<Message>| {
+            let mut counts = StatusCounts::default();
+
+            for (_, message) in database.iter_after(None) {
+                counts.add(message, now);
+            }
+
+            counts
+        };
+
+        let built = |builder: MessageBuilder| builder.body("Hello world").compose().unwrap();
+
+        let available = create_message!();
+        let delayed = create_message!(600);
+        let expired = built(MessageBuilder::default().ttl(0));
+        let exhausted = built(MessageBuilder::default().max_tries(0));
+        let delayed_expired = built(MessageBuilder::default().delay(600).ttl(0));
+        let reserved = built(MessageBuilder::default().max_tries(1));
+
+        for message in &[
+            &available,
+            &delayed,
+            &expired,
+            &exhausted,
+            &delayed_expired,
+            &reserved,
+        ] {
+            database.push_raw((*message).clone());
+        }
+
+        database
+            .reserve(reserved.id(), |message| message.reserve(now))
+            .unwrap();
+
+        let counts = database.status_counts(now);
+        assert_eq!(counts, counted(&database));
+        assert_eq!(
+            (
+                counts.available,
+                counts.transit,
+                counts.delayed,
+                counts.exhausted,
+                counts.expired
+            ),
+            (1, 1, 1, 1, 2)
+        );
+
+        // Requeued message without tries left becomes exhausted
+        database
+            .requeue(reserved.id(), |_| true, |message| message.requeue(now))
+            .unwrap();
+        database
+            .update(delayed.id(), |_| true, |message| message.postpone(now))
+            .unwrap();
+        database.delete_pos(expired.id()).unwrap();
+
+        let counts = database.status_counts(now);
+        assert_eq!(counts, counted(&database));
+        assert_eq!((counts.available, counts.exhausted), (2, 2));
+
+        database.clear();
+        assert_eq!(database.status_counts(now), StatusCounts::default());
+    }
+
+    #[test]
+    fn test_iter_after() {
+        let mut database = create_database();
+        let message1 = create_message!(5);
+        let message2 = create_message!();
+        let message3 = create_message!();
+        database.push_raw(message1.clone());
+        database.push_raw(message2.clone());
+        database.push_raw(message3.clone());
+        database
+            .reserve(message2.id(), |message| message.reserve(now()))
+            .unwrap();
+
+        let (cursor, message) = database.iter_after(None).next().unwrap();
+        assert_eq!(message.id(), message1.id());
+
+        database.delete_pos(message1.id()).unwrap();
+        assert_eq!(
+            database
+                .iter_after(Some(cursor))
+                .map(|(_, message)| message.id())
+                .collect::<Vec<_>>(),
+            vec![message2.id(), message3.id()]
+        );
+        assert!(database.iter_after(Some(cursor + 2)).next().is_none());
+    }
+
+    #[test]
+    fn test_update() {
+        let mut database = create_database();
+        let message1 = create_message!();
+        let message2 = create_message!(1);
+        database.push_raw(message1.clone());
+        database.push_raw(message2.clone());
+
+        database
+            .update(
+                message1.id(),
+                |_| true,
+                |message| message.postpone(now() + 2),
+            )
+            .unwrap();
+        assert_eq!(database.queue_tree.len(), 2);
+
+        position!(database, message2);
+        position!(database, message1);
+
+        let message = create_message!();
+        database.push_raw(message.clone());
+        database
+            .reserve(message.id(), |message| message.reserve(now()))
+            .unwrap();
+        assert!(database.update(message.id(), |_| true, |_| ()).is_none());
+    }
+
     #[test]
     fn test_get_mut() {
         let mut database = create_database();
@@ -242,7 +977,7 @@ mod tests {
         database.push_raw(message);
         let pos = database.position(|_| true).unwrap();
         let message = database.get_mut(pos).unwrap();
-        message.reserve();
+        message.reserve(now());
     }
 
     #[test]
@@ -276,6 +1011,22 @@ mod tests {
         assert_eq!(database.get(pos).unwrap().id(), message2.id());
     }
 
+    #[test]
+    fn test_drain_filter() {
+        let mut database = create_database();
+        let message1 = create_message!();
+        let message2 = create_message!();
+        database.push_raw(message1.clone());
+        database.push_raw(message2.clone());
+        let drained = database.drain_filter(|message| message.id() == message1.id());
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained.first().unwrap().id(), message1.id());
+        assert_eq!(database.objects.len(), 1);
+        assert_eq!(database.queue_tree.len(), 1);
+        let pos = database.position(|_| true).unwrap();
+        assert_eq!(database.get(pos).unwrap().id(), message2.id());
+    }
+
     #[test]
     fn test_len_clear() {
         let mut database = create_database();
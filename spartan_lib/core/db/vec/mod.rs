@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::core::{
-    db::{Database, StatusAwareDatabase},
-    payload::Identifiable,
+    db::{Database, StatusAwareDatabase, StatusCounts},
+    payload::{Dispatchable, Groupable, Identifiable, Status},
 };
 
 /// [`Vec`]-based database
@@ -12,15 +12,23 @@ use crate::core::{
 ///
 /// Currently used only for testing purposes due to simple implementation details
 ///
+/// Insertion id of each message is kept alongside it, so iteration cursors stay valid after removals.
+///
 /// [TreeDatabase]: super::TreeDatabase
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VecDatabase<M> {
+    last_insert_id: u64,
     db: Vec<M>,
+    insertions: Vec<u64>,
 }
 
 impl<M> Default for VecDatabase<M> {
     fn default() -> Self {
-        VecDatabase { db: Vec::new() }
+        VecDatabase {
+            last_insert_id: 0,
+            db: Vec::new(),
+            insertions: Vec::new(),
+        }
     }
 }
 
@@ -28,7 +36,9 @@ impl<M> Database<M> for VecDatabase<M> {
     type PositionKey = usize;
 
     fn push_raw(&mut self, message: M) {
+        self.last_insert_id += 1;
         self.db.push(message);
+        self.insertions.push(self.last_insert_id);
     }
 
     fn position<F>(&self, predicate: F) -> Option<Self::PositionKey>
@@ -46,8 +56,24 @@ impl<M> Database<M> for VecDatabase<M> {
         self.db.get_mut(position)
     }
 
+    fn iter_after(&self, cursor: Option<u64>) -> Box<dyn Iterator<Item = (u64, &M)> + '_> {
+        let skip = cursor.map_or(0, |cursor| {
+            self.insertions
+                .partition_point(|insert_id| *insert_id <= cursor)
+        });
+
+        Box::new(
+            self.insertions
+                .iter()
+                .copied()
+                .zip(self.db.iter())
+                .skip(skip),
+        )
+    }
+
     fn delete_pos(&mut self, position: Self::PositionKey) -> Option<M> {
         if self.db.get(position).is_some() {
+            self.insertions.remove(position);
             Some(self.db.remove(position))
         } else {
             None
@@ -58,7 +84,26 @@ impl<M> Database<M> for VecDatabase<M> {
     where
         F: Fn(&M) -> bool,
     {
-        self.db.retain(predicate);
+        self.drain_filter(|message| !predicate(message));
+    }
+
+    fn drain_filter<F>(&mut self, predicate: F) -> Vec<M>
+    where
+        F: Fn(&M) -> bool,
+    {
+        let mut drained = Vec::new();
+        let mut index = 0;
+
+        while index < self.db.len() {
+            if predicate(&self.db[index]) {
+                self.insertions.remove(index);
+                drained.push(self.db.remove(index));
+            } else {
+                index += 1;
+            }
+        }
+
+        drained
     }
 
     fn len(&self) -> usize {
@@ -72,31 +117,127 @@ impl<M> Database<M> for VecDatabase<M> {
     fn clear(&mut self) {
         self.db.clear();
         self.db.shrink_to_fit();
+        self.insertions.clear();
+        self.insertions.shrink_to_fit();
     }
 }
 
 impl<M> StatusAwareDatabase<M> for VecDatabase<M>
 where
-    M: Identifiable,
+    M: Identifiable + Groupable + Status + Dispatchable,
 {
     type RequeueKey = <M as Identifiable>::Id;
 
-    fn reserve(&mut self, position: Self::PositionKey) -> Option<&mut M> {
-        self.db.get_mut(position)
+    fn reserve<U>(&mut self, position: Self::PositionKey, update: U) -> Option<&mut M>
+    where
+        U: FnOnce(&mut M),
+    {
+        let message = self.db.get_mut(position)?;
+        update(message);
+        Some(message)
     }
 
-    fn requeue<F>(&mut self, position: Self::RequeueKey, predicate: F) -> Option<&mut M>
+    fn requeue<F, U>(
+        &mut self,
+        position: Self::RequeueKey,
+        predicate: F,
+        update: U,
+    ) -> Option<&mut M>
     where
         F: Fn(&M) -> bool,
+        U: FnOnce(&mut M),
     {
-        let message = self.reserve(self.position(|message| message.id() == position)?)?;
+        let message = self
+            .db
+            .iter_mut()
+            .find(|message| message.id() == position)?;
 
         if predicate(message) {
+            update(message);
             Some(message)
         } else {
             None
         }
     }
+
+    fn update<F, U>(
+        &mut self,
+        position: Self::RequeueKey,
+        predicate: F,
+        update: U,
+    ) -> Option<&mut M>
+    where
+        F: Fn(&M) -> bool,
+        U: FnOnce(&mut M),
+    {
+        let message = self
+            .db
+            .iter_mut()
+            .find(|message| message.id() == position)
+            .filter(|message| !message.requeueable() && predicate(message))?;
+
+        update(message);
+        Some(message)
+    }
+
+    fn reserved<F, U>(
+        &mut self,
+        position: Self::RequeueKey,
+        predicate: F,
+        update: U,
+    ) -> Option<&mut M>
+    where
+        F: Fn(&M) -> bool,
+        U: FnOnce(&mut M),
+    {
+        let message = self
+            .db
+            .iter_mut()
+            .find(|message| message.id() == position)
+            .filter(|message| predicate(message))?;
+
+        update(message);
+        Some(message)
+    }
+
+    fn status_counts(&self, now: i64) -> StatusCounts {
+        let mut counts = StatusCounts::default();
+
+        for message in &self.db {
+            counts.add(message, now);
+        }
+
+        counts
+    }
+
+    fn expired_reservations(&mut self, now: i64) -> Vec<Self::RequeueKey> {
+        self.db
+            .iter()
+            .filter(|message| matches!(message.reservation_deadline(), Some(deadline) if deadline < now))
+            .map(|message| message.id())
+            .collect()
+    }
+
+    fn expired_messages(&self, now: i64) -> Vec<Self::RequeueKey> {
+        self.db
+            .iter()
+            .filter(|message| {
+                message.reservable()
+                    && matches!(message.expires_at(), Some(expires_at) if expires_at <= now)
+            })
+            .map(|message| message.id())
+            .collect()
+    }
+
+    fn group_reserved(&self, message: &M) -> bool {
+        match message.group() {
+            Some(group) => self
+                .db
+                .iter()
+                .any(|message| message.group() == Some(group) && message.requeueable()),
+            None => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -150,6 +291,38 @@ mod tests {
         assert_eq!(db.get_mut(0).unwrap().id(), message.id());
     }
 
+    #[test]
+    fn test_iter_after() {
+        let mut db = create_database();
+        let message = create_message();
+        let message2 = create_message();
+        db.push_raw(message.clone());
+        db.push_raw(message2.clone());
+        assert_eq!(db.iter_after(None).count(), 2);
+        let (cursor, msg) = db.iter_after(None).next().unwrap();
+        assert_eq!(msg.id(), message.id());
+        let (cursor, msg) = db.iter_after(Some(cursor)).next().unwrap();
+        assert_eq!(msg.id(), message2.id());
+        assert!(db.iter_after(Some(cursor)).next().is_none());
+    }
+
+    #[test]
+    fn test_iter_after_delete() {
+        let mut db = create_database();
+        let message = create_message();
+        let message2 = create_message();
+        let message3 = create_message();
+        db.push_raw(message.clone());
+        db.push_raw(message2.clone());
+        db.push_raw(message3.clone());
+
+        let (cursor, _) = db.iter_after(None).nth(1).unwrap();
+        db.delete_pos(0).unwrap();
+
+        let (_, msg) = db.iter_after(Some(cursor)).next().unwrap();
+        assert_eq!(msg.id(), message3.id());
+    }
+
     #[test]
     fn test_delete() {
         let mut db = create_database();
@@ -171,6 +344,20 @@ mod tests {
         assert_eq!(db.len(), 1);
     }
 
+    #[test]
+    fn test_drain_filter() {
+        let mut db = create_database();
+        let message = create_message();
+        let message2 = create_message();
+        db.push_raw(message.clone());
+        db.push_raw(message2.clone());
+        let drained = db.drain_filter(|msg| msg.id() == message.id());
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained.first().unwrap().id(), message.id());
+        assert_eq!(db.len(), 1);
+        assert_eq!(db.get(0).unwrap().id(), message2.id());
+    }
+
     #[test]
     fn test_clear() {
         let mut db = create_database();
@@ -9,6 +9,7 @@ mod tests {
     #[macro_export]
     macro_rules! test_dispatcher {
         ($db:tt) => {
+            use chrono::Utc;
             use uuid::Uuid;
 
             use crate::core::{
@@ -29,6 +30,10 @@ mod tests {
                 $db::<Message>::default()
             }
 
+            fn now() -> i64 {
+                Utc::now().timestamp()
+            }
+
             #[test]
             fn push_message() {
                 let message = generate_test_message();
@@ -92,14 +97,14 @@ mod tests {
     #[macro_export]
     macro_rules! test_status_dispatcher {
         ($db:tt) => {
-            use crate::core::dispatcher::StatusAwareDispatcher;
+            use crate::core::{dispatcher::StatusAwareDispatcher, message::Patch};
 
             #[test]
             fn pop_message() {
                 let message = generate_test_message();
                 let mut db = create_database();
                 db.push(message.clone());
-                assert_eq!(db.pop().unwrap().id(), message.id());
+                assert_eq!(db.pop(now()).unwrap().id(), message.id());
             }
 
             #[test]
@@ -109,9 +114,9 @@ mod tests {
                 let mut db = create_database();
                 db.push(message1.clone());
                 db.push(message2.clone());
-                assert_eq!(db.pop().unwrap().id(), message1.id());
-                assert_eq!(db.pop().unwrap().id(), message2.id());
-                assert!(db.pop().is_none());
+                assert_eq!(db.pop(now()).unwrap().id(), message1.id());
+                assert_eq!(db.pop(now()).unwrap().id(), message2.id());
+                assert!(db.pop(now()).is_none());
             }
 
             #[test]
@@ -128,8 +133,8 @@ mod tests {
                 db.push(delayed_message);
                 db.push(message.clone());
 
-                assert_eq!(db.pop().unwrap().id(), message.id());
-                assert_eq!(db.pop().is_some(), false);
+                assert_eq!(db.pop(now()).unwrap().id(), message.id());
+                assert_eq!(db.pop(now()).is_some(), false);
             }
 
             #[test]
@@ -146,8 +151,8 @@ mod tests {
                 db.push(message.clone());
                 db.push(delayed_message.clone());
 
-                assert_eq!(db.pop().unwrap().id(), message.id());
-                assert_eq!(db.pop().unwrap().id(), delayed_message.id());
+                assert_eq!(db.pop(now()).unwrap().id(), message.id());
+                assert_eq!(db.pop(now()).unwrap().id(), delayed_message.id());
             }
 
             #[test]
@@ -160,13 +165,307 @@ mod tests {
                     .unwrap();
                 db.push(message);
 
-                let recv_message = db.pop().unwrap().id();
-                db.requeue(recv_message);
+                let recv_message = db.pop(now()).unwrap().id();
+                db.requeue(recv_message, now());
 
                 let message = generate_test_message();
                 db.push(message.clone());
 
-                assert_eq!(db.pop().unwrap().id(), message.id());
+                assert_eq!(db.pop(now()).unwrap().id(), message.id());
+            }
+
+            #[test]
+            fn requeue_delayed() {
+                let message1 = generate_test_message();
+                let message2 = generate_test_message();
+                let mut db = create_database();
+
+                db.push(message1.clone());
+                db.push(message2.clone());
+
+                let recv_message = db.pop(now()).unwrap().id();
+                assert_eq!(recv_message, message1.id());
+                db.requeue_delayed(recv_message, now() + 900, now())
+                    .unwrap();
+
+                assert_eq!(db.pop(now()).unwrap().id(), message2.id());
+                assert!(db.pop(now()).is_none());
+            }
+
+            #[test]
+            fn nack() {
+                let message1 = generate_test_message();
+                let message2 = generate_test_message();
+                let mut db = create_database();
+
+                db.push(message1.clone());
+                db.push(message2.clone());
+
+                let recv_message = db.pop(now()).unwrap().id();
+                db.nack(
+                    recv_message,
+                    Some("Connection refused".into()),
+                    Some(now() + 900),
+                    now(),
+                )
+                .unwrap();
+
+                assert!(db.nack(recv_message, None, None, now()).is_none());
+
+                assert_eq!(db.pop(now()).unwrap().id(), message2.id());
+                assert!(db.pop(now()).is_none());
+
+                let message = db.delete(recv_message).unwrap();
+                assert_eq!(&*message.state().failures()[0], "Connection refused");
+            }
+
+            #[test]
+            fn extend_timeout() {
+                let mut db = create_database();
+
+                let message = MessageBuilder::default()
+                    .body("Hello, world")
+                    .max_tries(3)
+                    .timeout(0)
+                    .compose()
+                    .unwrap();
+                db.push(message.clone());
+
+                assert!(db.extend(message.id(), None, now() + 600).is_none());
+
+                let recv_message = db.pop(now()).unwrap().id();
+                db.extend(recv_message, Some(600), now() + 600).unwrap();
+
+                db.gc();
+                assert_eq!(db.size(), 1);
+                assert!(db.pop(now()).is_none());
+            }
+
+            #[test]
+            fn update() {
+                let mut db = create_database();
+
+                let message1 = generate_test_message();
+                let message2 = generate_test_message();
+
+                db.push(message1.clone());
+                db.push(message2.clone());
+
+                db.update(message1.id(), |msg| {
+                    msg.apply(Patch {
+                        available_at: Some(now() + 900),
+                        ..Default::default()
+                    })
+                })
+                .unwrap();
+
+                let recv_message = db.pop(now()).unwrap().id();
+                assert_eq!(recv_message, message2.id());
+                assert!(db.pop(now()).is_none());
+                assert!(db.update(recv_message, |_| ()).is_none());
+            }
+
+            #[test]
+            fn pop_many() {
+                let mut db = create_database();
+
+                let grouped = |body| {
+                    MessageBuilder::default()
+                        .body(body)
+                        .max_tries(3)
+                        .group_id("group")
+                        .compose()
+                        .unwrap()
+                };
+
+                let message1 = grouped("first");
+                let message2 = grouped("second");
+                let message3 = generate_test_message();
+                let message4 = generate_test_message();
+
+                db.push(message1.clone());
+                db.push(message2.clone());
+                db.push(message3.clone());
+                db.push(message4.clone());
+
+                let popped: Vec<_> = db
+                    .pop_many(2, now())
+                    .into_iter()
+                    .map(|msg| msg.id())
+                    .collect();
+                assert_eq!(popped, vec![message1.id(), message3.id()]);
+
+                // Second message of group is not available, while first one is reserved
+                let popped: Vec<_> = db
+                    .pop_many(5, now())
+                    .into_iter()
+                    .map(|msg| msg.id())
+                    .collect();
+                assert_eq!(popped, vec![message4.id()]);
+
+                assert!(db.pop_many(0, now()).is_empty());
+            }
+
+            #[test]
+            fn extend_reclaim() {
+                let mut db = create_database();
+
+                let timed = || {
+                    MessageBuilder::default()
+                        .body("Hello, world")
+                        .max_tries(3)
+                        .timeout(0)
+                        .compose()
+                        .unwrap()
+                };
+
+                let message1 = timed();
+                let message2 = timed();
+                db.push(message1.clone());
+                db.push(message2.clone());
+
+                db.pop(now()).unwrap();
+                db.pop(now()).unwrap();
+                db.extend(message2.id(), Some(600), now() + 600).unwrap();
+
+                let later = now() + 1;
+
+                assert_eq!(db.timed_out_ids(later), vec![message1.id()]);
+                db.time_out(message1.id(), later).unwrap();
+                assert!(db.timed_out_ids(later).is_empty());
+                assert_eq!(db.pop(later).unwrap().id(), message1.id());
+
+                db.time_out(message2.id(), now()).unwrap();
+                assert!(db.time_out(message2.id(), now()).is_none());
+            }
+
+            #[test]
+            fn extend_shortened() {
+                let mut db = create_database();
+
+                let message = MessageBuilder::default()
+                    .body("Hello, world")
+                    .max_tries(3)
+                    .timeout(600)
+                    .compose()
+                    .unwrap();
+                db.push(message.clone());
+
+                let now = now();
+
+                db.pop(now).unwrap();
+                db.extend(message.id(), None, now - 1).unwrap();
+
+                // Timed out reservation is returned to queue, not collected
+                db.gc();
+                assert_eq!(db.size(), 1);
+
+                assert_eq!(db.timed_out_ids(now), vec![message.id()]);
+                db.time_out(message.id(), now).unwrap();
+                assert_eq!(db.pop(now).unwrap().id(), message.id());
+            }
+
+            #[test]
+            fn timed_out_ids() {
+                let mut db = create_database();
+
+                let message = MessageBuilder::default()
+                    .body("Hello, world")
+                    .max_tries(3)
+                    .timeout(0)
+                    .compose()
+                    .unwrap();
+                db.push(message.clone());
+
+                db.pop(now()).unwrap();
+
+                let later = now() + 1;
+
+                assert_eq!(db.timed_out_ids(later), vec![message.id()]);
+                assert_eq!(db.timed_out_ids(later), vec![message.id()]);
+
+                db.time_out(message.id(), later).unwrap();
+                assert!(db.timed_out_ids(later).is_empty());
+            }
+
+            #[test]
+            fn expired_ids() {
+                let mut db = create_database();
+
+                let expiring = || {
+                    MessageBuilder::default()
+                        .body("Hello, world")
+                        .max_tries(3)
+                        .ttl(600)
+                        .compose()
+                        .unwrap()
+                };
+
+                let message = expiring();
+                let reserved = expiring();
+                db.push(reserved.clone());
+                db.push(message.clone());
+                db.push(generate_test_message());
+
+                let now = now();
+
+                db.pop(now).unwrap();
+
+                assert!(db.expired_ids(now).is_empty());
+                assert_eq!(db.expired_ids(now + 600), vec![message.id()]);
+                assert_eq!(db.size(), 3);
+            }
+
+            #[test]
+            fn exhausted_ids() {
+                let mut db = create_database();
+
+                let message = MessageBuilder::default()
+                    .body("Hello, world")
+                    .compose()
+                    .unwrap();
+                db.push(message.clone());
+                db.push(generate_test_message());
+
+                let recv_message = db.pop(now()).unwrap().id();
+                db.requeue(recv_message, now());
+
+                assert_eq!(db.exhausted_ids(), vec![message.id()]);
+                assert_eq!(db.size(), 2);
+            }
+
+            #[test]
+            fn group_pop() {
+                let mut db = create_database();
+
+                let grouped = |body| {
+                    MessageBuilder::default()
+                        .body(body)
+                        .max_tries(3)
+                        .group_id("group")
+                        .compose()
+                        .unwrap()
+                };
+
+                let message1 = grouped("first");
+                let message2 = grouped("second");
+                let message3 = generate_test_message();
+
+                db.push(message1.clone());
+                db.push(message2.clone());
+                db.push(message3.clone());
+
+                assert_eq!(db.pop(now()).unwrap().id(), message1.id());
+                assert_eq!(db.pop(now()).unwrap().id(), message3.id());
+                assert!(db.pop(now()).is_none());
+
+                db.requeue(message1.id(), now()).unwrap();
+                assert_eq!(db.pop(now()).unwrap().id(), message1.id());
+                assert!(db.pop(now()).is_none());
+
+                db.delete(message1.id()).unwrap();
+                assert_eq!(db.pop(now()).unwrap().id(), message2.id());
+                assert!(db.pop(now()).is_none());
             }
         };
     }
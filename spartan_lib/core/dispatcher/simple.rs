@@ -45,23 +45,14 @@ where
     /// use spartan_lib::core::dispatcher::SimpleDispatcher;
     /// use spartan_lib::core::db::VecDatabase;
     /// use spartan_lib::core::message::builder::MessageBuilder;
-    /// use spartan_lib::core::payload::{Identifiable, Status};
-    /// use std::thread::sleep;
-    /// use std::time::Duration;
+    /// use spartan_lib::core::payload::Identifiable;
     ///
     /// let mut db = VecDatabase::default();
     ///
-    /// // We need two test messages here: one without timeout, and one with 1 second timeout
-    /// // GC condition varies between messages, but here we'll use timeout as an example
+    /// // We need two test messages here: one without TTL, and one with TTL, that is already over
+    /// // GC condition varies between messages, but here we'll use TTL as an example
     /// let message = MessageBuilder::default().body("Hello, world").compose().unwrap();
-    ///
-    /// // Setting timeout to 0 and waiting 1-2 seconds turns message into garbage
-    /// let mut garbage_message = MessageBuilder::default().body("I will be deleted").timeout(0).compose().unwrap();
-    ///
-    /// // We are going to reserve the message before adding it do database
-    /// garbage_message.reserve();
-    ///
-    /// sleep(Duration::from_secs(2));
+    /// let garbage_message = MessageBuilder::default().body("I will be deleted").ttl(0).compose().unwrap();
     ///
     /// db.push(message.clone());
     /// db.push(garbage_message);
@@ -5,6 +5,9 @@ use crate::core::{
 };
 
 /// Interface for working with databases that support statuses
+///
+/// Methods, that change message status, accept current time as UTC timestamp,
+/// so replaying the same change later gives the same result.
 pub trait StatusAwareDispatcher<M>: SimpleDispatcher<M>
 where
     M: Status,
@@ -13,7 +16,10 @@ where
     ///
     /// Behaves like "peek", but with "obtainable" message check, message and database reservation
     ///
+    /// Messages, whose group already has a reserved message, are skipped
+    ///
     /// ```
+    /// use chrono::Utc;
     /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
     /// use spartan_lib::core::db::TreeDatabase;
     /// use spartan_lib::core::message::builder::MessageBuilder;
@@ -24,29 +30,270 @@ where
     ///
     /// db.push(message.clone());
     ///
-    /// assert_eq!(db.pop().unwrap().id(), message.id());
+    /// assert_eq!(db.pop(Utc::now().timestamp()).unwrap().id(), message.id());
     /// ```
-    fn pop(&mut self) -> Option<&M>;
+    fn pop(&mut self, now: i64) -> Option<&M>;
+
+    /// Pop up to `n` messages from queue
+    ///
+    /// Behaves like calling "pop" `n` times, but stops as soon as there are no messages available
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Identifiable;
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let message = MessageBuilder::default().body("Hello").compose().unwrap();
+    /// let next = MessageBuilder::default().body("world").compose().unwrap();
+    ///
+    /// db.push(message.clone());
+    /// db.push(next.clone());
+    ///
+    /// let ids: Vec<_> = db.pop_many(3, Utc::now().timestamp()).into_iter().map(|msg| msg.id()).collect();
+    ///
+    /// assert_eq!(ids, vec![message.id(), next.id()]);
+    /// ```
+    fn pop_many(&mut self, n: usize, now: i64) -> Vec<&M>;
 
     /// Requeue message in queue
     ///
     /// Returns None, if message was not found, or message cannot be requeued
     ///
     /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Identifiable;
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let now = Utc::now().timestamp();
+    /// let message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    ///
+    /// db.push(message);
+    ///
+    /// let id = db.pop(now).unwrap().id();
+    /// db.requeue(id, now).unwrap();
+    /// ```
+    fn requeue(&mut self, id: <M as Identifiable>::Id, now: i64) -> Option<()>;
+
+    /// Requeue message in queue, and postpone it until provided UTC timestamp
+    ///
+    /// Message is re-sorted in queue according to its new delay.
+    /// Returns None, if message was not found, or message cannot be requeued
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Identifiable;
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let now = Utc::now().timestamp();
+    /// let message = MessageBuilder::default().body("Hello, world").max_tries(2).compose().unwrap();
+    ///
+    /// db.push(message);
+    ///
+    /// let id = db.pop(now).unwrap().id();
+    /// db.requeue_delayed(id, now + 600, now).unwrap();
+    ///
+    /// assert!(db.pop(now).is_none());
+    /// ```
+    fn requeue_delayed(
+        &mut self,
+        id: <M as Identifiable>::Id,
+        available_at: i64,
+        now: i64,
+    ) -> Option<()>;
+
+    /// Negatively acknowledge message, requeueing it with optional failure reason and delay
+    ///
+    /// Failure reason is recorded in message, and message is postponed until provided UTC timestamp, if there is one.
+    /// Returns None, if message was not found, or message cannot be requeued
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Identifiable;
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let now = Utc::now().timestamp();
+    /// let message = MessageBuilder::default().body("Hello, world").max_tries(2).compose().unwrap();
+    ///
+    /// db.push(message);
+    ///
+    /// let id = db.pop(now).unwrap().id();
+    /// db.nack(id, Some("Connection refused".into()), Some(now + 600), now).unwrap();
+    ///
+    /// assert!(db.pop(now).is_none());
+    /// ```
+    fn nack(
+        &mut self,
+        id: <M as Identifiable>::Id,
+        reason: Option<Box<str>>,
+        available_at: Option<i64>,
+        now: i64,
+    ) -> Option<()>;
+
+    /// Extend timeout of message, that is currently being processed
+    ///
+    /// Moves reservation deadline to provided UTC timestamp, and replaces max timeout if new one is provided.
+    /// Returns None, if message was not found, or message is not reserved
+    ///
+    /// ```
+    /// use chrono::Utc;
     /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
     /// use spartan_lib::core::db::TreeDatabase;
     /// use spartan_lib::core::message::builder::MessageBuilder;
     /// use spartan_lib::core::payload::Identifiable;
     ///
     /// let mut db = TreeDatabase::default();
+    /// let now = Utc::now().timestamp();
     /// let message = MessageBuilder::default().body("Hello, world").compose().unwrap();
     ///
     /// db.push(message);
     ///
-    /// let id = db.pop().unwrap().id();
-    /// db.requeue(id).unwrap();
+    /// let id = db.pop(now).unwrap().id();
+    /// db.extend(id, Some(600), now + 600).unwrap();
     /// ```
-    fn requeue(&mut self, id: <M as Identifiable>::Id) -> Option<()>;
+    fn extend(
+        &mut self,
+        id: <M as Identifiable>::Id,
+        timeout: Option<u32>,
+        deadline: i64,
+    ) -> Option<()>;
+
+    /// Update message, that is currently not being processed
+    ///
+    /// Message is re-indexed after update, so it may change message delay or priority.
+    /// Returns None, if message was not found, or message is reserved
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::{builder::MessageBuilder, Patch};
+    /// use spartan_lib::core::payload::Identifiable;
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    /// let id = message.id();
+    ///
+    /// db.push(message);
+    ///
+    /// db.update(id, |msg| msg.apply(Patch { available_at: Some(Utc::now().timestamp() + 600), ..Default::default() })).unwrap();
+    ///
+    /// assert!(db.pop(Utc::now().timestamp()).is_none());
+    /// ```
+    fn update<U>(&mut self, id: <M as Identifiable>::Id, update: U) -> Option<()>
+    where
+        U: FnOnce(&mut M);
+
+    /// Obtain IDs of messages, whose reservation timed out before provided UTC timestamp
+    ///
+    /// Messages are left reserved, so reclaim can be recorded before it's applied with [`time_out`].
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Identifiable;
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let message = MessageBuilder::default().body("Hello, world").max_tries(2).timeout(0).compose().unwrap();
+    /// let now = Utc::now().timestamp();
+    ///
+    /// db.push(message.clone());
+    /// db.pop(now).unwrap();
+    ///
+    /// assert_eq!(db.timed_out_ids(now + 1), vec![message.id()]);
+    /// assert!(db.pop(now + 1).is_none());
+    /// ```
+    ///
+    /// [`time_out`]: StatusAwareDispatcher::time_out
+    fn timed_out_ids(&mut self, now: i64) -> Vec<<M as Identifiable>::Id>;
+
+    /// Return reserved message back to queue as timed out
+    ///
+    /// Reservation deadline is not checked, so this method may be used to replay recorded reclaim.
+    /// Returns None, if message was not found, or message is not reserved
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Identifiable;
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let now = Utc::now().timestamp();
+    /// let message = MessageBuilder::default().body("Hello, world").max_tries(2).compose().unwrap();
+    ///
+    /// db.push(message);
+    ///
+    /// let id = db.pop(now).unwrap().id();
+    /// db.time_out(id, now).unwrap();
+    ///
+    /// assert_eq!(db.pop(now).unwrap().id(), id);
+    /// ```
+    fn time_out(&mut self, id: <M as Identifiable>::Id, now: i64) -> Option<()>;
+
+    /// Obtain IDs of expired messages, that block queue from dispatching other messages
+    ///
+    /// Expired messages are not obtainable, thus [`TreeDatabase`] is blocked by them until they are removed.
+    /// Messages are left in queue, so removal can be recorded before it's applied.
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Identifiable;
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let message = MessageBuilder::default().body("Hello, world").ttl(0).compose().unwrap();
+    ///
+    /// db.push(message.clone());
+    ///
+    /// assert_eq!(db.expired_ids(Utc::now().timestamp()), vec![message.id()]);
+    /// assert_eq!(db.size(), 1);
+    /// ```
+    ///
+    /// [`TreeDatabase`]: crate::core::db::TreeDatabase
+    fn expired_ids(&self, now: i64) -> Vec<<M as Identifiable>::Id>;
+
+    /// Obtain IDs of exhausted messages
+    ///
+    /// Exhausted message is a garbage message, that has no tries left, or has expired TTL.
+    /// Messages are left in queue, so removal can be recorded before it's applied.
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::dispatcher::{SimpleDispatcher, StatusAwareDispatcher};
+    /// use spartan_lib::core::db::TreeDatabase;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Identifiable;
+    ///
+    /// let mut db = TreeDatabase::default();
+    /// let now = Utc::now().timestamp();
+    /// let message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    ///
+    /// db.push(message.clone());
+    ///
+    /// let id = db.pop(now).unwrap().id();
+    /// db.requeue(id, now).unwrap();
+    ///
+    /// assert_eq!(db.exhausted_ids(), vec![message.id()]);
+    /// assert_eq!(db.size(), 1);
+    /// ```
+    fn exhausted_ids(&self) -> Vec<<M as Identifiable>::Id>;
 }
 
 impl<T, M> StatusAwareDispatcher<M> for T
@@ -54,16 +301,126 @@ where
     T: SimpleDispatcher<M> + StatusAwareDatabase<M, RequeueKey = <M as Identifiable>::Id>,
     M: Status,
 {
-    fn pop(&mut self) -> Option<&M> {
-        let position = self.position(|msg| msg.reservable() && msg.obtainable())?;
-        let message = self.reserve(position).unwrap();
-        message.reserve();
-        Some(message)
+    fn pop(&mut self, now: i64) -> Option<&M> {
+        let position =
+            self.position(|msg| msg.reservable() && msg.obtainable() && !self.group_reserved(msg))?;
+        self.reserve(position, |msg| msg.reserve(now))
+            .map(|message| &*message)
+    }
+
+    fn pop_many(&mut self, n: usize, now: i64) -> Vec<&M> {
+        let mut positions = Vec::new();
+
+        while positions.len() < n {
+            match self
+                .position(|msg| msg.reservable() && msg.obtainable() && !self.group_reserved(msg))
+            {
+                Some(position) => {
+                    self.reserve(position, |msg| msg.reserve(now)).unwrap();
+                    positions.push(position);
+                }
+                None => break,
+            }
+        }
+
+        let database = &*self;
+
+        positions
+            .into_iter()
+            .filter_map(|position| database.get(position))
+            .collect()
+    }
+
+    fn requeue(&mut self, key: <M as Identifiable>::Id, now: i64) -> Option<()> {
+        self.requeue(
+            key,
+            |msg| msg.requeueable() && msg.obtainable(),
+            |msg| msg.requeue(now),
+        )?;
+        Some(())
     }
 
-    fn requeue(&mut self, key: <M as Identifiable>::Id) -> Option<()> {
-        let message = self.requeue(key, |msg| msg.requeueable() && msg.obtainable())?;
-        message.requeue();
+    fn requeue_delayed(
+        &mut self,
+        key: <M as Identifiable>::Id,
+        available_at: i64,
+        now: i64,
+    ) -> Option<()> {
+        self.requeue(
+            key,
+            |msg| msg.requeueable() && msg.obtainable(),
+            |msg| {
+                msg.requeue(now);
+                msg.postpone(available_at);
+            },
+        )?;
         Some(())
     }
+
+    fn nack(
+        &mut self,
+        key: <M as Identifiable>::Id,
+        reason: Option<Box<str>>,
+        available_at: Option<i64>,
+        now: i64,
+    ) -> Option<()> {
+        self.requeue(
+            key,
+            |msg| msg.requeueable() && msg.obtainable(),
+            |msg| {
+                msg.requeue(now);
+
+                if let Some(reason) = reason {
+                    msg.fail(reason);
+                }
+
+                if let Some(available_at) = available_at {
+                    msg.postpone(available_at);
+                }
+            },
+        )?;
+        Some(())
+    }
+
+    fn extend(
+        &mut self,
+        key: <M as Identifiable>::Id,
+        timeout: Option<u32>,
+        deadline: i64,
+    ) -> Option<()> {
+        self.reserved(
+            key,
+            |msg| msg.requeueable(),
+            |msg| msg.extend(timeout, deadline),
+        )?;
+        Some(())
+    }
+
+    fn update<U>(&mut self, key: <M as Identifiable>::Id, update: U) -> Option<()>
+    where
+        U: FnOnce(&mut M),
+    {
+        self.update(key, |_| true, update)?;
+        Some(())
+    }
+
+    fn timed_out_ids(&mut self, now: i64) -> Vec<<M as Identifiable>::Id> {
+        self.expired_reservations(now)
+    }
+
+    fn time_out(&mut self, key: <M as Identifiable>::Id, now: i64) -> Option<()> {
+        self.requeue(key, |msg| msg.requeueable(), |msg| msg.time_out(now))?;
+        Some(())
+    }
+
+    fn expired_ids(&self, now: i64) -> Vec<<M as Identifiable>::Id> {
+        self.expired_messages(now)
+    }
+
+    fn exhausted_ids(&self) -> Vec<<M as Identifiable>::Id> {
+        self.iter_after(None)
+            .filter(|(_, msg)| msg.gc() && (!msg.has_tries() || msg.expired()))
+            .map(|(_, msg)| msg.id())
+            .collect()
+    }
 }
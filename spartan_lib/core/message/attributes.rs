@@ -0,0 +1,129 @@
+use std::collections::{btree_map::Iter, BTreeMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::message::builder::BuilderError;
+
+/// Max amount of attributes, that a single message can have
+pub const MAX_ATTRIBUTES: usize = 16;
+
+/// Max attribute key length in bytes
+pub const MAX_ATTRIBUTE_KEY_LENGTH: usize = 128;
+
+/// Max attribute value length in bytes
+pub const MAX_ATTRIBUTE_VALUE_LENGTH: usize = 1024;
+
+/// Message attributes
+///
+/// Key-value storage of message metadata (content type, trace IDs, routing keys, etc.),
+/// that is kept separately from message body.
+///
+/// Attributes are validated by [`MessageBuilder`] against [`MAX_ATTRIBUTES`],
+/// [`MAX_ATTRIBUTE_KEY_LENGTH`] and [`MAX_ATTRIBUTE_VALUE_LENGTH`] limits.
+///
+/// [`MessageBuilder`]: crate::core::message::builder::MessageBuilder
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Attributes(BTreeMap<Box<str>, Box<str>>);
+
+impl Attributes {
+    pub(crate) fn insert(&mut self, key: Box<str>, value: Box<str>) {
+        self.0.insert(key, value);
+    }
+
+    /// Check if attributes match message limits
+    pub(crate) fn validate(&self) -> Result<(), BuilderError> {
+        if self.0.len() > MAX_ATTRIBUTES {
+            return Err(BuilderError::TooManyAttributes);
+        }
+
+        for (key, value) in self.0.iter() {
+            if key.is_empty() || key.len() > MAX_ATTRIBUTE_KEY_LENGTH {
+                return Err(BuilderError::InvalidAttributeKey);
+            }
+
+            if value.len() > MAX_ATTRIBUTE_VALUE_LENGTH {
+                return Err(BuilderError::AttributeValueTooLong);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get attribute value by key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|value| &**value)
+    }
+
+    /// Iterate over attributes in key order
+    pub fn iter(&self) -> Iter<'_, Box<str>, Box<str>> {
+        self.0.iter()
+    }
+
+    /// Get amount of attributes
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if there are no attributes
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Attributes, MAX_ATTRIBUTES, MAX_ATTRIBUTE_KEY_LENGTH, MAX_ATTRIBUTE_VALUE_LENGTH};
+
+    fn attribute(key: &str, value: &str) -> (Box<str>, Box<str>) {
+        (key.into(), value.into())
+    }
+
+    #[test]
+    fn test_get() {
+        let mut attributes = Attributes::default();
+        let (key, value) = attribute("content-type", "application/json");
+        attributes.insert(key, value);
+        assert_eq!(attributes.get("content-type"), Some("application/json"));
+        assert_eq!(attributes.get("trace-id"), None);
+        assert_eq!(attributes.len(), 1);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut attributes = Attributes::default();
+        assert!(attributes.validate().is_ok());
+
+        for index in 0..MAX_ATTRIBUTES {
+            let (key, value) = attribute(&index.to_string(), "value");
+            attributes.insert(key, value);
+        }
+
+        assert!(attributes.validate().is_ok());
+
+        let (key, value) = attribute("overflow", "value");
+        attributes.insert(key, value);
+        assert!(attributes.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        let mut attributes = Attributes::default();
+        let (key, value) = attribute("", "value");
+        attributes.insert(key, value);
+        assert!(attributes.validate().is_err());
+
+        let mut attributes = Attributes::default();
+        let (key, value) = attribute(&"k".repeat(MAX_ATTRIBUTE_KEY_LENGTH + 1), "value");
+        attributes.insert(key, value);
+        assert!(attributes.validate().is_err());
+    }
+
+    #[test]
+    fn test_value_too_long() {
+        let mut attributes = Attributes::default();
+        let (key, value) = attribute("key", &"v".repeat(MAX_ATTRIBUTE_VALUE_LENGTH + 1));
+        attributes.insert(key, value);
+        assert!(attributes.validate().is_err());
+    }
+}
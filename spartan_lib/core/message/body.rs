@@ -0,0 +1,219 @@
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    ops::Deref,
+    str::from_utf8,
+};
+
+use serde::{
+    de::{Deserializer, Error, SeqAccess, Visitor},
+    Deserialize, Serialize, Serializer,
+};
+
+/// Binary message body
+///
+/// Stores raw bytes, so any payload (protobuf, compressed data, etc.) may be used as message body.
+///
+/// Binary formats (like `bincode`) store body bytes as-is.
+/// Human-readable formats (like JSON) receive body as a string, if it contains valid UTF-8,
+/// and as an array of bytes otherwise.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Body(Box<[u8]>);
+
+impl Body {
+    /// Get body bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Get body as string slice
+    ///
+    /// [`None`] if body is not valid UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        from_utf8(&self.0).ok()
+    }
+}
+
+impl Deref for Body {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Body {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Debug for Body {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.as_str() {
+            Some(body) => Debug::fmt(body, f),
+            None => Debug::fmt(&self.0, f),
+        }
+    }
+}
+
+impl PartialEq<str> for Body {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<[u8]> for Body {
+    fn eq(&self, other: &[u8]) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl From<Box<[u8]>> for Body {
+    fn from(body: Box<[u8]>) -> Self {
+        Body(body)
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(body: Vec<u8>) -> Self {
+        Body(body.into_boxed_slice())
+    }
+}
+
+impl From<&[u8]> for Body {
+    fn from(body: &[u8]) -> Self {
+        Body(body.into())
+    }
+}
+
+impl From<Box<str>> for Body {
+    fn from(body: Box<str>) -> Self {
+        Body(body.into_boxed_bytes())
+    }
+}
+
+impl From<String> for Body {
+    fn from(body: String) -> Self {
+        Body(body.into_bytes().into_boxed_slice())
+    }
+}
+
+impl From<&str> for Body {
+    fn from(body: &str) -> Self {
+        Body(body.as_bytes().into())
+    }
+}
+
+impl Serialize for Body {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.as_str() {
+            Some(body) if serializer.is_human_readable() => serializer.serialize_str(body),
+            _ => serializer.serialize_bytes(&self.0),
+        }
+    }
+}
+
+struct BodyVisitor;
+
+impl<'de> Visitor<'de> for BodyVisitor {
+    type Value = Body;
+
+    fn expecting(&self, formatter: &mut Formatter) -> FmtResult {
+        write!(formatter, "a string or a sequence of bytes")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Body::from(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Body::from(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Body::from(v))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Body::from(v))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut body = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+        while let Some(byte) = seq.next_element()? {
+            body.push(byte);
+        }
+
+        Ok(Body::from(body))
+    }
+}
+
+impl<'de> Deserialize<'de> for Body {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(BodyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Body;
+
+    #[test]
+    fn test_str_body() {
+        let body = Body::from("Hello, world");
+        assert_eq!(body.as_str(), Some("Hello, world"));
+        assert_eq!(body, *"Hello, world");
+    }
+
+    #[test]
+    fn test_binary_body() {
+        let body = Body::from(vec![0, 159, 146, 150]);
+        assert!(body.as_str().is_none());
+        assert_eq!(body.as_bytes(), &[0, 159, 146, 150]);
+    }
+
+    #[test]
+    fn test_bincode_compatibility() {
+        let body = Body::from("Hello, world");
+        let legacy: Box<str> = String::from("Hello, world").into_boxed_str();
+
+        let serialized = bincode::serialize(&body).unwrap();
+        assert_eq!(serialized, bincode::serialize(&legacy).unwrap());
+        assert_eq!(bincode::deserialize::<Body>(&serialized).unwrap(), body);
+    }
+
+    #[test]
+    fn test_json() {
+        let body = Body::from("Hello, world");
+        let serialized = serde_json::to_string(&body).unwrap();
+        assert_eq!(serialized, "\"Hello, world\"");
+        assert_eq!(serde_json::from_str::<Body>(&serialized).unwrap(), body);
+
+        let body = Body::from(vec![0, 159, 146, 150]);
+        let serialized = serde_json::to_string(&body).unwrap();
+        assert_eq!(serialized, "[0,159,146,150]");
+        assert_eq!(serde_json::from_str::<Body>(&serialized).unwrap(), body);
+    }
+}
@@ -1,6 +1,13 @@
+use chrono::{DateTime, Duration, FixedOffset, LocalResult, NaiveDateTime, Offset as _, TimeZone};
+use chrono_tz::Tz;
 use thiserror::Error;
+use uuid::Uuid;
 
-use crate::core::message::{time::Offset, Message};
+use crate::core::message::{
+    attributes::Attributes,
+    time::{Offset, Time},
+    Body, Message,
+};
 
 #[derive(Debug, Error)]
 pub enum BuilderError {
@@ -8,49 +15,106 @@ pub enum BuilderError {
     BodyNotProvided,
     #[error("Offset must be in range of -86399 and 86399 seconds")]
     OffsetOutOfBounds,
+    #[error("Unknown IANA timezone name")]
+    InvalidTimezone,
+    #[error("Message can't have more than 16 attributes")]
+    TooManyAttributes,
+    #[error("Attribute key must be non-empty and not longer than 128 bytes")]
+    InvalidAttributeKey,
+    #[error("Attribute value must not be longer than 1024 bytes")]
+    AttributeValueTooLong,
+    #[error("Delivery time offset must match message offset")]
+    DeliveryOffsetMismatch,
+    #[error("Delivery time must not be in the past")]
+    DeliveryTimeInPast,
+    #[error("Delay and delivery time can't be set together")]
+    DelayWithDeliveryTime,
 }
 
 /// Message builder
 ///
 /// ```
-/// use spartan_lib::chrono::{Utc, FixedOffset};
+/// use spartan_lib::chrono::{Duration, Utc, FixedOffset};
 /// use spartan_lib::core::message::builder::MessageBuilder;
 ///
 /// let message = MessageBuilder::default()
 ///     .body("Hello, world")
-///     .offset(9 * 3600)
+///     .timezone("Asia/Tokyo")
 ///     .max_tries(5)
 ///     .timeout(60)
 ///     .delay(10)
+///     .ttl(3600)
+///     .attribute("content-type", "text/plain")
+///     .priority(1)
+///     .group_id("order-1")
+///     .compose()
+///     .unwrap();
+///
+/// let scheduled = MessageBuilder::default()
+///     .body("Hello, world")
+///     .offset(9 * 3600)
+///     .deliver_at(Utc::now().with_timezone(&FixedOffset::east_opt(9 * 3600).unwrap()) + Duration::hours(1))
+///     .compose()
+///     .unwrap();
+///
+/// let local = MessageBuilder::default()
+///     .body("Hello, world")
+///     .timezone("Europe/Berlin")
+///     .deliver_at_local("2030-03-31T09:00:00".parse().unwrap())
 ///     .compose()
 ///     .unwrap();
 /// ```
 pub struct MessageBuilder {
-    body: Option<Box<str>>,
+    id: Option<Uuid>,
+    body: Option<Body>,
     offset: i32,
+    timezone: Option<Box<str>>,
     max_tries: u32,
     timeout: u32,
     delay: Option<u32>,
+    deliver_at: Option<DateTime<FixedOffset>>,
+    deliver_at_local: Option<NaiveDateTime>,
+    allow_past_delivery: bool,
+    ttl: Option<u32>,
+    attributes: Attributes,
+    priority: u8,
+    group_id: Option<Box<str>>,
 }
 
 impl Default for MessageBuilder {
     fn default() -> Self {
         MessageBuilder {
+            id: None,
             body: None,
             offset: 0,
+            timezone: None,
             max_tries: 1,
             timeout: 30,
             delay: None,
+            deliver_at: None,
+            deliver_at_local: None,
+            allow_past_delivery: false,
+            ttl: None,
+            attributes: Attributes::default(),
+            priority: 0,
+            group_id: None,
         }
     }
 }
 
 impl MessageBuilder {
-    /// Message body.
+    /// Message ID. If not provided, random ID is generated.
+    #[must_use]
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Message body. Accepts both strings and raw bytes.
     #[must_use]
     pub fn body<T>(mut self, body: T) -> Self
     where
-        T: Into<Box<str>>,
+        T: Into<Body>,
     {
         self.body = Some(body.into());
         self
@@ -63,6 +127,18 @@ impl MessageBuilder {
         self
     }
 
+    /// IANA timezone name, e.g. `Europe/Berlin`. Replaces offset.
+    ///
+    /// Message times are calculated with timezone offset at their moment, so DST changes are respected.
+    #[must_use]
+    pub fn timezone<T>(mut self, timezone: T) -> Self
+    where
+        T: Into<Box<str>>,
+    {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
     /// Max tries for message to be reserved.
     #[must_use]
     pub fn max_tries(mut self, max_tries: u32) -> Self {
@@ -77,22 +153,138 @@ impl MessageBuilder {
         self
     }
 
-    /// Set message delay in seconds.
+    /// Set message delay in seconds. Replaces delivery time.
     #[must_use]
     pub fn delay(mut self, delay: u32) -> Self {
         self.delay = Some(delay);
+        self.deliver_at = None;
+        self.deliver_at_local = None;
+        self
+    }
+
+    /// Set absolute message delivery time. Replaces delay.
+    ///
+    /// Delivery time offset must match message offset, or timezone offset at delivery time.
+    #[must_use]
+    pub fn deliver_at(mut self, deliver_at: DateTime<FixedOffset>) -> Self {
+        self.deliver_at = Some(deliver_at);
+        self.deliver_at_local = None;
+        self.delay = None;
+        self
+    }
+
+    /// Set message delivery time as local wall-clock time of message timezone, or of message offset. Replaces delay.
+    ///
+    /// Ambiguous time, that occurs twice because of DST change, resolves to its first occurrence.
+    /// Nonexistent time, that is skipped by DST change, is moved forward by the length of skipped interval.
+    #[must_use]
+    pub fn deliver_at_local(mut self, deliver_at: NaiveDateTime) -> Self {
+        self.deliver_at_local = Some(deliver_at);
+        self.deliver_at = None;
+        self.delay = None;
         self
     }
 
-    /// Compose message. Returns Err, if body was not provided.
+    /// Allow delivery time to be in the past. Such messages are available right away.
+    #[must_use]
+    pub fn allow_past_delivery(mut self, allow_past_delivery: bool) -> Self {
+        self.allow_past_delivery = allow_past_delivery;
+        self
+    }
+
+    /// Set message TTL in seconds, starting from message creation.
+    ///
+    /// Expired messages are never dispatched, and are collected by GC.
+    #[must_use]
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Add message attribute. Existing attribute with the same key is replaced.
+    #[must_use]
+    pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Box<str>>,
+        V: Into<Box<str>>,
+    {
+        self.attributes.insert(key.into(), value.into());
+        self
+    }
+
+    /// Message priority. Messages with higher priority are dispatched first.
+    #[must_use]
+    pub fn priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Message group. Messages of the same group are dispatched one at a time, in queue order.
+    #[must_use]
+    pub fn group_id<T>(mut self, group_id: T) -> Self
+    where
+        T: Into<Box<str>>,
+    {
+        self.group_id = Some(group_id.into());
+        self
+    }
+
+    /// Compose message. Returns Err, if body was not provided, or if any of parameters is invalid.
     pub fn compose(self) -> Result<Message, BuilderError> {
         if let Some(body) = self.body {
+            self.attributes.validate()?;
+
+            let offset = Offset::new(self.offset).ok_or(BuilderError::OffsetOutOfBounds)?;
+
+            let timezone = match self.timezone {
+                Some(timezone) => Some(
+                    timezone
+                        .parse::<Tz>()
+                        .map_err(|_| BuilderError::InvalidTimezone)?,
+                ),
+                None => None,
+            };
+
+            let mut time = Time::new(offset, timezone, self.delay, self.timeout, self.ttl);
+
+            let deliver_at = match (self.deliver_at, self.deliver_at_local) {
+                (_, Some(local)) => Some(match timezone {
+                    Some(timezone) => resolve_local(&timezone, local),
+                    None => resolve_local(&FixedOffset::east(offset.get()), local),
+                }),
+                (deliver_at, None) => deliver_at,
+            };
+
+            if let Some(deliver_at) = deliver_at {
+                // With named timezone, offset must match timezone offset at delivery time
+                let expected_offset = match timezone {
+                    Some(timezone) => deliver_at
+                        .with_timezone(&timezone)
+                        .offset()
+                        .fix()
+                        .local_minus_utc(),
+                    None => offset.get(),
+                };
+
+                if deliver_at.offset().local_minus_utc() != expected_offset {
+                    return Err(BuilderError::DeliveryOffsetMismatch);
+                }
+
+                if !self.allow_past_delivery && deliver_at < *time.dispatched_at() {
+                    return Err(BuilderError::DeliveryTimeInPast);
+                }
+
+                time.schedule(deliver_at);
+            }
+
             Ok(Message::new(
+                self.id.unwrap_or_else(Message::generate_id),
                 body,
-                self.delay,
-                Offset::new(self.offset).ok_or(BuilderError::OffsetOutOfBounds)?,
+                time,
                 self.max_tries,
-                self.timeout,
+                self.attributes,
+                self.priority,
+                self.group_id,
             ))
         } else {
             Err(BuilderError::BodyNotProvided)
@@ -100,9 +292,39 @@ impl MessageBuilder {
     }
 }
 
+/// Resolve local wall-clock time in provided timezone
+///
+/// Ambiguous time resolves to its first occurrence, and nonexistent time is resolved with offset,
+/// that was in effect before the gap, which moves it forward by the length of the gap.
+fn resolve_local<T>(timezone: &T, local: NaiveDateTime) -> DateTime<FixedOffset>
+where
+    T: TimeZone,
+{
+    let resolved = match timezone.from_local_datetime(&local) {
+        LocalResult::Single(datetime) | LocalResult::Ambiguous(datetime, _) => datetime,
+        LocalResult::None => {
+            // Timezones don't change offset twice within a day, so offset a day before is the one before the gap
+            let before = timezone
+                .offset_from_utc_datetime(&(local - Duration::days(1)))
+                .fix();
+
+            timezone.from_utc_datetime(
+                &(local - Duration::seconds(i64::from(before.local_minus_utc()))),
+            )
+        }
+    };
+
+    resolved.with_timezone(&resolved.offset().fix())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::MessageBuilder;
+    use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, Utc};
+
+    use uuid::Uuid;
+
+    use super::{BuilderError, MessageBuilder};
+    use crate::core::payload::{Dispatchable, Identifiable};
 
     #[test]
     fn creates_message() {
@@ -112,10 +334,203 @@ mod tests {
             .offset(100)
             .delay(1)
             .timeout(40)
+            .ttl(60)
             .compose()
             .unwrap();
     }
 
+    #[test]
+    fn creates_message_with_id() {
+        let id = Uuid::new_v4();
+
+        let message = MessageBuilder::default()
+            .id(id)
+            .body("Hello, world")
+            .compose()
+            .unwrap();
+
+        assert_eq!(message.id(), id);
+    }
+
+    #[test]
+    fn creates_binary_message() {
+        let message = MessageBuilder::default()
+            .body(vec![0u8, 159, 146, 150])
+            .compose()
+            .unwrap();
+
+        assert_eq!(message.body().as_bytes(), &[0, 159, 146, 150]);
+    }
+
+    #[test]
+    fn creates_message_with_attributes() {
+        let message = MessageBuilder::default()
+            .body("Hello, world")
+            .attribute("content-type", "text/plain")
+            .attribute("trace-id", "abc")
+            .compose()
+            .unwrap();
+
+        assert_eq!(message.attributes().get("content-type"), Some("text/plain"));
+        assert_eq!(message.attributes().len(), 2);
+    }
+
+    #[test]
+    fn fails_with_invalid_attribute() {
+        assert!(MessageBuilder::default()
+            .body("Hello, world")
+            .attribute("", "value")
+            .compose()
+            .is_err());
+    }
+
+    #[test]
+    fn creates_scheduled_message() {
+        let deliver_at =
+            Utc::now().with_timezone(&FixedOffset::east_opt(3600).unwrap()) + Duration::hours(1);
+
+        let message = MessageBuilder::default()
+            .body("Hello, world")
+            .offset(3600)
+            .deliver_at(deliver_at)
+            .compose()
+            .unwrap();
+
+        assert_eq!(message.time().delay(), &Some(deliver_at));
+        assert!(!message.obtainable());
+    }
+
+    #[test]
+    fn fails_with_invalid_delivery_time() {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+
+        assert!(matches!(
+            MessageBuilder::default()
+                .body("Hello, world")
+                .offset(3600)
+                .deliver_at(now + Duration::hours(1))
+                .compose(),
+            Err(BuilderError::DeliveryOffsetMismatch)
+        ));
+
+        assert!(matches!(
+            MessageBuilder::default()
+                .body("Hello, world")
+                .deliver_at(now - Duration::hours(1))
+                .compose(),
+            Err(BuilderError::DeliveryTimeInPast)
+        ));
+
+        let message = MessageBuilder::default()
+            .body("Hello, world")
+            .deliver_at(now - Duration::hours(1))
+            .allow_past_delivery(true)
+            .compose()
+            .unwrap();
+
+        assert!(message.obtainable());
+    }
+
+    #[test]
+    fn creates_message_with_timezone() {
+        let message = MessageBuilder::default()
+            .body("Hello, world")
+            .timezone("Europe/Berlin")
+            .delay(10)
+            .compose()
+            .unwrap();
+
+        assert_eq!(message.time().timezone(), Some(chrono_tz::Europe::Berlin));
+
+        let summer = DateTime::parse_from_rfc3339("2030-07-15T09:00:00+02:00").unwrap();
+
+        let message = MessageBuilder::default()
+            .body("Hello, world")
+            .timezone("Europe/Berlin")
+            .deliver_at(summer)
+            .compose()
+            .unwrap();
+
+        assert_eq!(message.time().delay(), &Some(summer));
+
+        assert!(matches!(
+            MessageBuilder::default()
+                .body("Hello, world")
+                .timezone("Europe/Berlin")
+                .deliver_at(summer.with_timezone(&FixedOffset::east_opt(3600).unwrap()))
+                .compose(),
+            Err(BuilderError::DeliveryOffsetMismatch)
+        ));
+
+        assert!(matches!(
+            MessageBuilder::default()
+                .body("Hello, world")
+                .timezone("Mars/Olympus")
+                .compose(),
+            Err(BuilderError::InvalidTimezone)
+        ));
+    }
+
+    #[test]
+    fn creates_message_with_local_delivery_time() {
+        let local = |datetime: &str| datetime.parse::<NaiveDateTime>().unwrap();
+
+        let scheduled = |timezone: &str, datetime: &str| {
+            *MessageBuilder::default()
+                .body("Hello, world")
+                .timezone(timezone)
+                .deliver_at_local(local(datetime))
+                .compose()
+                .unwrap()
+                .time()
+                .delay()
+        };
+
+        // The same wall-clock time is resolved with winter and summer offsets
+        assert_eq!(
+            scheduled("Europe/Berlin", "2030-01-15T09:00:00"),
+            Some(DateTime::parse_from_rfc3339("2030-01-15T09:00:00+01:00").unwrap())
+        );
+
+        assert_eq!(
+            scheduled("Europe/Berlin", "2030-07-15T09:00:00"),
+            Some(DateTime::parse_from_rfc3339("2030-07-15T09:00:00+02:00").unwrap())
+        );
+
+        // Ambiguous time resolves to its first occurrence
+        assert_eq!(
+            scheduled("Europe/Berlin", "2030-10-27T02:30:00"),
+            Some(DateTime::parse_from_rfc3339("2030-10-27T02:30:00+02:00").unwrap())
+        );
+
+        // Nonexistent time is moved forward by the length of the gap
+        assert_eq!(
+            scheduled("Europe/Berlin", "2030-03-31T02:30:00"),
+            Some(DateTime::parse_from_rfc3339("2030-03-31T03:30:00+02:00").unwrap())
+        );
+
+        let message = MessageBuilder::default()
+            .body("Hello, world")
+            .offset(3600)
+            .deliver_at_local(local("2030-01-15T09:00:00"))
+            .compose()
+            .unwrap();
+
+        assert_eq!(
+            message.time().delay(),
+            &Some(DateTime::parse_from_rfc3339("2030-01-15T09:00:00+01:00").unwrap())
+        );
+
+        assert!(matches!(
+            MessageBuilder::default()
+                .body("Hello, world")
+                .timezone("Europe/Berlin")
+                .deliver_at_local(local("2000-01-15T09:00:00"))
+                .compose(),
+            Err(BuilderError::DeliveryTimeInPast)
+        ));
+    }
+
     #[test]
     #[should_panic]
     fn fails_with_empty_body() {
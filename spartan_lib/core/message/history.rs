@@ -0,0 +1,132 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use super::time::serialization::{tz_local_seconds, tz_local_seconds_option};
+
+/// Amount of the latest reservations, that are kept in message history
+const MAX_ATTEMPTS: usize = 10;
+
+/// Outcome of message reservation
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Message was deleted after processing
+    Deleted,
+
+    /// Message was returned to queue
+    Requeued,
+
+    /// Message was not returned before its timeout
+    TimedOut,
+}
+
+/// Single message reservation, with its obtain time and outcome
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Attempt {
+    #[serde(with = "tz_local_seconds")]
+    obtained_at: DateTime<FixedOffset>,
+    outcome: Option<Outcome>,
+    #[serde(with = "tz_local_seconds_option")]
+    finished_at: Option<DateTime<FixedOffset>>,
+}
+
+impl Attempt {
+    /// Get reservation time
+    pub fn obtained_at(&self) -> &DateTime<FixedOffset> {
+        &self.obtained_at
+    }
+
+    /// Get reservation outcome
+    ///
+    /// [`None`] if message is still being processed
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// Get time of reservation outcome
+    ///
+    /// [`None`] if message is still being processed
+    pub fn finished_at(&self) -> &Option<DateTime<FixedOffset>> {
+        &self.finished_at
+    }
+}
+
+/// History of the latest message reservations, oldest first
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct History {
+    attempts: Vec<Attempt>,
+}
+
+impl History {
+    pub(crate) fn obtain(&mut self, current_time: DateTime<FixedOffset>) {
+        if self.attempts.len() == MAX_ATTEMPTS {
+            self.attempts.remove(0);
+        }
+
+        self.attempts.push(Attempt {
+            obtained_at: current_time,
+            outcome: None,
+            finished_at: None,
+        });
+    }
+
+    pub(crate) fn finish(&mut self, outcome: Outcome, current_time: DateTime<FixedOffset>) {
+        if let Some(attempt) = self
+            .attempts
+            .last_mut()
+            .filter(|attempt| attempt.outcome.is_none())
+        {
+            attempt.outcome = Some(outcome);
+            attempt.finished_at = Some(current_time);
+        }
+    }
+
+    /// Get message reservations
+    ///
+    /// Only the last 10 reservations are kept
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, Utc};
+
+    use super::{History, Outcome, MAX_ATTEMPTS};
+
+    fn now() -> DateTime<FixedOffset> {
+        Utc::now().into()
+    }
+
+    #[test]
+    fn finish() {
+        let mut history = History::default();
+        history.finish(Outcome::Deleted, now());
+        assert!(history.attempts().is_empty());
+
+        history.obtain(now());
+        assert_eq!(history.attempts()[0].outcome(), None);
+
+        history.finish(Outcome::Requeued, now());
+        history.finish(Outcome::Deleted, now());
+        assert_eq!(history.attempts()[0].outcome(), Some(Outcome::Requeued));
+        assert!(history.attempts()[0].finished_at().is_some());
+    }
+
+    #[test]
+    fn bounded() {
+        let mut history = History::default();
+        let first = now();
+
+        for _ in 0..=MAX_ATTEMPTS {
+            history.obtain(now());
+            history.finish(Outcome::TimedOut, now());
+        }
+
+        history.obtain(first);
+
+        assert_eq!(history.attempts().len(), MAX_ATTEMPTS);
+        assert_eq!(history.attempts()[MAX_ATTEMPTS - 1].obtained_at(), &first);
+        assert_eq!(history.attempts()[MAX_ATTEMPTS - 1].outcome(), None);
+    }
+}
@@ -1,18 +1,39 @@
 /// Message builder
 pub mod builder;
 
+/// Message attributes
+pub mod attributes;
+
+/// Message body
+mod body;
+
 /// Message time manager
 mod time;
 
 /// Message internal state
 mod state;
 
+/// Message reservation history
+mod history;
+
+/// Changes of available message
+mod patch;
+
+use std::cmp::Reverse;
+
+use attributes::Attributes;
+pub use body::Body;
+use chrono::DateTime;
+pub use history::{Attempt, History, Outcome};
+pub use patch::Patch;
 use serde::{Deserialize, Serialize};
 pub use state::{State, Status};
 pub use time::{Offset, Time, Timeout};
 use uuid::Uuid;
 
-use crate::core::payload::{Dispatchable, Identifiable, Sortable, Status as StatusPayload};
+use crate::core::payload::{
+    Dispatchable, Groupable, Identifiable, Sortable, Status as StatusPayload,
+};
 
 /// Default message implementation, with support of all [`payload`] traits
 ///
@@ -23,24 +44,34 @@ use crate::core::payload::{Dispatchable, Identifiable, Sortable, Status as Statu
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Message {
     id: Uuid,
-    body: Box<str>,
+    body: Body,
     state: State,
     time: Time,
+    history: History,
+    attributes: Attributes,
+    priority: u8,
+    group_id: Option<Box<str>>,
 }
 
 impl Message {
     fn new(
-        body: Box<str>,
-        delay: Option<u32>,
-        offset: Offset,
+        id: Uuid,
+        body: Body,
+        time: Time,
         max_tries: u32,
-        timeout: u32,
+        attributes: Attributes,
+        priority: u8,
+        group_id: Option<Box<str>>,
     ) -> Self {
         Message {
-            id: Message::generate_id(),
+            id,
             body,
             state: State::new(max_tries),
-            time: Time::new(offset, delay, timeout),
+            time,
+            history: History::default(),
+            attributes,
+            priority,
+            group_id,
         }
     }
 
@@ -61,6 +92,63 @@ impl Message {
     pub fn time(&self) -> &Time {
         &self.time
     }
+
+    /// Get message reservation [`History`]
+    ///
+    /// [`History`]: history::History
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Get message [`Attributes`]
+    ///
+    /// [`Attributes`]: attributes::Attributes
+    pub fn attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    /// Get message priority
+    ///
+    /// Messages with higher priority are dispatched first
+    pub fn priority(&self) -> &u8 {
+        &self.priority
+    }
+
+    /// Replace message ID with newly generated one
+    ///
+    /// Used to move message to queue, that already has message with the same ID
+    pub fn regenerate_id(&mut self) {
+        self.id = Self::generate_id();
+    }
+
+    /// Apply changes to message
+    ///
+    /// Delay is counted from current time, and max tries can't be lower than current tries
+    pub fn apply(&mut self, patch: Patch) {
+        if let Some(body) = patch.body {
+            self.body = body;
+        }
+
+        if let Some(available_at) = patch.available_at {
+            self.time.postpone(available_at);
+        }
+
+        if let Some(max_tries) = patch.max_tries {
+            self.state.set_max_tries(max_tries);
+        }
+
+        if let Some(timeout) = patch.timeout {
+            self.time.set_timeout(timeout);
+        }
+    }
+}
+
+impl Groupable for Message {
+    type Group = Box<str>;
+
+    fn group(&self) -> Option<&Box<str>> {
+        self.group_id.as_ref()
+    }
 }
 
 impl Identifiable for Message {
@@ -72,10 +160,14 @@ impl Identifiable for Message {
 }
 
 impl Dispatchable for Message {
-    type Body = str;
+    type Body = Body;
 
     fn obtainable(&self) -> bool {
-        self.time.check_delay() && !self.time.expired()
+        // Timeout is checked only for reserved message, since message,
+        // that was returned to queue, keeps time of its last reservation
+        self.time.check_delay()
+            && !(self.state.requeueable() && self.time.expired())
+            && self.time.check_ttl()
     }
 
     fn body(&self) -> &Self::Body {
@@ -83,18 +175,43 @@ impl Dispatchable for Message {
     }
 
     fn gc(&self) -> bool {
-        self.state.requires_gc() || (self.state.requeueable() && self.time.expired())
+        // Timed out reservation is returned to queue by reclaim, so reserved message is never garbage
+        self.state.requires_gc() || (!self.state.requeueable() && self.expired())
+    }
+
+    fn expired(&self) -> bool {
+        !self.time.check_ttl()
+    }
+
+    fn available_at(&self) -> Option<i64> {
+        self.time.get_raw_delay()
+    }
+
+    fn expires_at(&self) -> Option<i64> {
+        self.time.expires_at().as_ref().map(DateTime::timestamp)
+    }
+
+    fn dispatched_at(&self) -> i64 {
+        self.time.dispatched_at().timestamp()
     }
 }
 
 impl StatusPayload for Message {
-    fn requeue(&mut self) {
+    fn requeue(&mut self, now: i64) {
         self.state.requeue();
+        self.history
+            .finish(Outcome::Requeued, self.time.datetime_at(now));
     }
 
-    fn reserve(&mut self) {
+    fn reserve(&mut self, now: i64) {
         self.state.reserve();
-        self.time.obtain();
+        self.time.obtain(now);
+        self.history.obtain(self.time.datetime_at(now));
+    }
+
+    fn acknowledge(&mut self, now: i64) {
+        self.history
+            .finish(Outcome::Deleted, self.time.datetime_at(now));
     }
 
     fn requeueable(&self) -> bool {
@@ -108,20 +225,63 @@ impl StatusPayload for Message {
     fn has_tries(&self) -> bool {
         self.state.has_tries()
     }
+
+    fn dead_letter(&mut self, now: i64) {
+        // Reserved message can only be moved to dead letter queue after its timeout
+        if self.state.requeueable() {
+            self.history
+                .finish(Outcome::TimedOut, self.time.datetime_at(now));
+        }
+
+        self.state.dead_letter();
+        self.time.clear_ttl();
+    }
+
+    fn postpone(&mut self, available_at: i64) {
+        self.time.postpone(available_at);
+    }
+
+    fn fail(&mut self, reason: Box<str>) {
+        self.state.fail(reason);
+    }
+
+    fn extend(&mut self, timeout: Option<u32>, deadline: i64) {
+        self.time.extend(timeout, deadline);
+    }
+
+    fn reservation_deadline(&self) -> Option<i64> {
+        if self.state.requeueable() {
+            self.time.deadline()
+        } else {
+            None
+        }
+    }
+
+    fn time_out(&mut self, now: i64) {
+        self.state.requeue();
+        self.history
+            .finish(Outcome::TimedOut, self.time.datetime_at(now));
+    }
 }
 
 impl Sortable for Message {
-    type Sort = Option<i64>;
+    type Sort = (Reverse<u8>, Option<i64>);
 
     fn sort(&self) -> Self::Sort {
-        self.time.get_raw_delay()
+        (Reverse(self.priority), self.time.get_raw_delay())
+    }
+
+    fn next_partition(sort: &Self::Sort) -> Option<Self::Sort> {
+        Some(((sort.0).0.checked_sub(1).map(Reverse)?, None))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::builder::MessageBuilder;
-    use crate::core::payload::Sortable;
+    use chrono::Utc;
+
+    use super::{builder::MessageBuilder, Outcome, Patch};
+    use crate::core::payload::{Dispatchable, Sortable, Status};
 
     macro_rules! delayed_message {
         ($time:expr) => {
@@ -144,4 +304,86 @@ mod tests {
         assert_eq!(vec.pop().unwrap().id, message1.id);
         assert_eq!(vec.pop().unwrap().id, message3.id);
     }
+
+    #[test]
+    fn test_priority_sort() {
+        let message1 = delayed_message!(1);
+        let message2 = MessageBuilder::default()
+            .delay(2)
+            .priority(1)
+            .body("Hello world")
+            .compose()
+            .unwrap();
+        let mut vec = vec![message1.clone(), message2.clone()];
+        vec.sort_by_key(|msg| msg.sort());
+        assert_eq!(vec.pop().unwrap().id, message1.id);
+        assert_eq!(vec.pop().unwrap().id, message2.id);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut message = MessageBuilder::default()
+            .body("Hello world")
+            .max_tries(2)
+            .compose()
+            .unwrap();
+
+        let now = Utc::now().timestamp();
+
+        message.reserve(now);
+        message.requeue(now + 1);
+        message.reserve(now + 2);
+        message.dead_letter(now + 3);
+
+        let finished: Vec<_> = message
+            .history()
+            .attempts()
+            .iter()
+            .map(|attempt| {
+                attempt
+                    .finished_at()
+                    .map(|finished_at| finished_at.timestamp())
+            })
+            .collect();
+
+        assert_eq!(finished, [Some(now + 1), Some(now + 3)]);
+        assert_eq!(
+            message.history().attempts()[1].obtained_at().timestamp(),
+            now + 2
+        );
+
+        let outcomes: Vec<_> = message
+            .history()
+            .attempts()
+            .iter()
+            .map(|attempt| attempt.outcome())
+            .collect();
+
+        assert_eq!(outcomes, [Some(Outcome::Requeued), Some(Outcome::TimedOut)]);
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut message = MessageBuilder::default()
+            .body("Hello world")
+            .max_tries(2)
+            .compose()
+            .unwrap();
+
+        let now = Utc::now().timestamp();
+
+        message.reserve(now);
+        message.requeue(now);
+        message.apply(Patch {
+            body: Some("Updated".into()),
+            available_at: Some(now + 600),
+            max_tries: Some(0),
+            timeout: Some(60),
+        });
+
+        assert_eq!(*message.body(), *"Updated");
+        assert!(!message.obtainable());
+        assert_eq!(*message.state().max_tries(), 1);
+        assert_eq!(*message.time().timeout().max(), 60);
+    }
 }
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use super::Body;
+
+/// Changes, that can be applied to message, that is not reserved
+///
+/// Fields, that are [`None`], are left unchanged
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Patch {
+    /// New message body
+    pub body: Option<Body>,
+
+    /// UTC timestamp, until which message is postponed
+    ///
+    /// Timestamp is absolute, so replaying the same change later gives the same result
+    pub available_at: Option<i64>,
+
+    /// New max tries
+    pub max_tries: Option<u32>,
+
+    /// New max timeout in seconds
+    pub timeout: Option<u32>,
+}
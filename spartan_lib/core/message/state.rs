@@ -16,12 +16,16 @@ impl Default for Status {
     }
 }
 
-/// Message state, containing try count and status
+/// Amount of the latest failure reasons, that are kept in message state
+const MAX_FAILURES: usize = 10;
+
+/// Message state, containing try count, status and failure history
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct State {
     status: Status,
     tries: u32,
     max_tries: u32,
+    failures: Vec<Box<str>>,
 }
 
 impl State {
@@ -30,6 +34,7 @@ impl State {
             status: Status::default(),
             tries: 0,
             max_tries,
+            failures: Vec::new(),
         }
     }
 
@@ -41,6 +46,14 @@ impl State {
         self.status = Status::Available;
     }
 
+    pub(crate) fn fail(&mut self, reason: Box<str>) {
+        if self.failures.len() == MAX_FAILURES {
+            self.failures.remove(0);
+        }
+
+        self.failures.push(reason);
+    }
+
     pub(crate) fn reserve(&mut self) {
         self.status = Status::Transit;
         self.tries += 1;
@@ -54,6 +67,16 @@ impl State {
         self.status == Status::Available && self.has_tries()
     }
 
+    pub(crate) fn dead_letter(&mut self) {
+        self.status = Status::Available;
+        self.max_tries = self.tries + self.max_tries.max(1);
+    }
+
+    /// Replace max tries, that can't be lower than current tries
+    pub(crate) fn set_max_tries(&mut self, max_tries: u32) {
+        self.max_tries = max_tries.max(self.tries);
+    }
+
     pub(crate) fn requires_gc(&self) -> bool {
         self.tries == self.max_tries && self.status == Status::Available
     }
@@ -79,11 +102,18 @@ impl State {
     pub fn max_tries(&self) -> &u32 {
         &self.max_tries
     }
+
+    /// Get reasons of the latest message processing failures, oldest first
+    ///
+    /// Only the last 10 reasons are kept
+    pub fn failures(&self) -> &[Box<str>] {
+        &self.failures
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::State;
+    use super::{State, MAX_FAILURES};
 
     #[test]
     fn create_valid_state() {
@@ -108,4 +138,33 @@ mod tests {
         state.requeue();
         assert!(state.requires_gc());
     }
+
+    #[test]
+    fn dead_letter() {
+        let mut state = State::new(2);
+        state.reserve();
+        state.requeue();
+        state.reserve();
+        assert!(!state.has_tries());
+        state.dead_letter();
+        assert!(state.reservable());
+        assert_eq!(*state.tries(), 2);
+        assert_eq!(*state.max_tries(), 4);
+    }
+
+    #[test]
+    fn failures() {
+        let mut state = State::new(1);
+
+        for i in 0..=MAX_FAILURES {
+            state.fail(i.to_string().into_boxed_str());
+        }
+
+        assert_eq!(state.failures().len(), MAX_FAILURES);
+        assert_eq!(&*state.failures()[0], "1");
+        assert_eq!(
+            &*state.failures()[MAX_FAILURES - 1],
+            &*MAX_FAILURES.to_string()
+        );
+    }
 }
@@ -1,4 +1,5 @@
-use chrono::{DateTime, Duration, FixedOffset, Utc};
+use chrono::{DateTime, Duration, FixedOffset, Offset as _, TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
 /// Message timeout options
@@ -23,10 +24,26 @@ impl Timeout {
         self.obtained_at = Some(current_time);
     }
 
+    /// Move deadline, keeping obtain time consistent with max timeout
+    pub(super) fn extend(&mut self, deadline: DateTime<FixedOffset>, max: Option<u32>) {
+        if let Some(max) = max {
+            self.max = max;
+        }
+
+        self.obtained_at = Some(deadline - Duration::seconds(i64::from(self.max)));
+    }
+
+    pub(super) fn set_max(&mut self, max: u32) {
+        self.max = max;
+    }
+
     pub(super) fn expired(&self, current_time: DateTime<FixedOffset>) -> bool {
-        self.obtained_at.map_or(false, |obtained_at| {
-            (obtained_at + Duration::seconds(i64::from(self.max))) < current_time
-        })
+        matches!(self.deadline(), Some(deadline) if deadline < current_time)
+    }
+
+    pub(super) fn deadline(&self) -> Option<DateTime<FixedOffset>> {
+        self.obtained_at
+            .map(|obtained_at| obtained_at + Duration::seconds(i64::from(self.max)))
     }
 
     /// Get max timeout in seconds
@@ -67,6 +84,9 @@ impl Offset {
 /// A time manager for handling message dispatch times, timeouts,
 /// delays and timezones
 ///
+/// If message has named timezone, then all times are calculated with timezone offset
+/// at their moment, so DST changes are respected. Otherwise, fixed offset is used.
+///
 /// Be aware, that all time handling itself is accessible to [`Message`] only
 ///
 /// [`Message`]: crate::core::message::Message
@@ -74,6 +94,8 @@ impl Offset {
 pub struct Time {
     offset: Offset,
 
+    timezone: Option<Tz>,
+
     #[serde(with = "serialization::tz_local_seconds")]
     dispatched_at: DateTime<FixedOffset>,
 
@@ -81,18 +103,35 @@ pub struct Time {
     delay: Option<DateTime<FixedOffset>>,
 
     timeout: Timeout,
+
+    #[serde(with = "serialization::tz_local_seconds_option")]
+    expires_at: Option<DateTime<FixedOffset>>,
 }
 
 impl Time {
-    pub(crate) fn new(offset: Offset, delay: Option<u32>, timeout: u32) -> Time {
-        let dispatched_at = Self::get_datetime_with_offset(offset.get());
-
-        Time {
+    pub(crate) fn new(
+        offset: Offset,
+        timezone: Option<Tz>,
+        delay: Option<u32>,
+        timeout: u32,
+        ttl: Option<u32>,
+    ) -> Time {
+        let mut time = Time {
             offset,
-            dispatched_at,
-            delay: Self::convert_delay(delay.map(i64::from), dispatched_at),
+            timezone,
+            dispatched_at: Self::get_datetime_with_offset(offset.get()),
+            delay: None,
             timeout: Timeout::new(timeout),
-        }
+            expires_at: None,
+        };
+
+        // Named timezone replaces fixed offset with timezone offset at dispatch time
+        time.dispatched_at = time.get_datetime();
+        time.offset = Offset(time.dispatched_at.offset().local_minus_utc());
+        time.delay = time.convert_delay(delay.map(i64::from), time.dispatched_at);
+        time.expires_at = time.convert_delay(ttl.map(i64::from), time.dispatched_at);
+
+        time
     }
 
     pub(crate) fn check_delay(&self) -> bool {
@@ -100,23 +139,60 @@ impl Time {
             .map_or(true, |delay| delay <= self.get_datetime())
     }
 
+    pub(crate) fn check_ttl(&self) -> bool {
+        !matches!(self.expires_at, Some(expires_at) if expires_at <= self.get_datetime())
+    }
+
+    pub(crate) fn clear_ttl(&mut self) {
+        self.expires_at = None;
+    }
+
     pub(crate) fn get_raw_delay(&self) -> Option<i64> {
         self.delay.as_ref().map(DateTime::timestamp)
     }
 
-    pub(crate) fn obtain(&mut self) {
-        self.timeout.obtain(self.get_datetime());
+    pub(crate) fn schedule(&mut self, deliver_at: DateTime<FixedOffset>) {
+        self.delay = Some(self.localize(deliver_at));
+    }
+
+    pub(crate) fn postpone(&mut self, available_at: i64) {
+        self.schedule(self.datetime_at(available_at));
+    }
+
+    pub(crate) fn obtain(&mut self, now: i64) {
+        self.timeout.obtain(self.datetime_at(now));
+    }
+
+    pub(crate) fn extend(&mut self, timeout: Option<u32>, deadline: i64) {
+        self.timeout.extend(self.datetime_at(deadline), timeout);
+    }
+
+    pub(crate) fn set_timeout(&mut self, timeout: u32) {
+        self.timeout.set_max(timeout);
     }
 
     pub(crate) fn expired(&self) -> bool {
         self.timeout.expired(self.get_datetime())
     }
 
+    pub(crate) fn deadline(&self) -> Option<i64> {
+        self.timeout.deadline().as_ref().map(DateTime::timestamp)
+    }
+
     /// Get message timezone offset.
+    ///
+    /// If message has named timezone, then timezone offset at dispatch time is returned.
     pub fn offset(&self) -> &Offset {
         &self.offset
     }
 
+    /// Get message named timezone.
+    ///
+    /// [`None`] if message uses fixed offset.
+    pub fn timezone(&self) -> Option<Tz> {
+        self.timezone
+    }
+
     /// Get message dispatch time with offset awareness.
     pub fn dispatched_at(&self) -> &DateTime<FixedOffset> {
         &self.dispatched_at
@@ -139,15 +215,44 @@ impl Time {
         &self.timeout
     }
 
+    /// Get message expiration time.
+    ///
+    /// If `current time >= expires_at`, then message is no longer available for obtaining, and is collected by GC.
+    ///
+    /// [`None`] if message has no TTL.
+    pub fn expires_at(&self) -> &Option<DateTime<FixedOffset>> {
+        &self.expires_at
+    }
+
     fn convert_delay(
+        &self,
         seconds: Option<i64>,
         dispatched_at: DateTime<FixedOffset>,
     ) -> Option<DateTime<FixedOffset>> {
-        Some(dispatched_at + Duration::seconds(seconds?))
+        Some(self.localize(dispatched_at + Duration::seconds(seconds?)))
+    }
+
+    /// Convert time to named timezone offset at that moment, if message has one
+    fn localize(&self, datetime: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self.timezone {
+            Some(timezone) => {
+                let local = datetime.with_timezone(&timezone);
+                local.with_timezone(&local.offset().fix())
+            }
+            None => datetime,
+        }
     }
 
-    fn get_datetime(&self) -> DateTime<FixedOffset> {
-        Self::get_datetime_with_offset(self.offset.get())
+    /// Convert UTC timestamp to time with message offset, or named timezone offset at that moment
+    pub(crate) fn datetime_at(&self, timestamp: i64) -> DateTime<FixedOffset> {
+        self.localize(
+            Utc.timestamp(timestamp, 0)
+                .with_timezone(&FixedOffset::east(self.offset.get())),
+        )
+    }
+
+    pub(crate) fn get_datetime(&self) -> DateTime<FixedOffset> {
+        self.localize(Self::get_datetime_with_offset(self.offset.get()))
     }
 
     fn get_datetime_with_offset(offset: i32) -> DateTime<FixedOffset> {
@@ -155,7 +260,7 @@ impl Time {
     }
 }
 
-mod serialization {
+pub(super) mod serialization {
     use std::fmt::{Formatter, Result as FmtResult};
 
     use chrono::{DateTime, FixedOffset, TimeZone, Utc};
@@ -292,19 +397,91 @@ mod tests {
         assert!(timeout.expired(timestamp + ChronoDuration::seconds(4)));
     }
 
+    #[test]
+    fn test_timeout_extend() {
+        let timestamp = get_timestamp();
+        let mut timeout = Timeout::new(3);
+        timeout.obtain(timestamp);
+        timeout.extend(timestamp + ChronoDuration::seconds(5), None);
+        assert!(!timeout.expired(timestamp + ChronoDuration::seconds(4)));
+        assert_eq!(
+            timeout.deadline(),
+            Some(timestamp + ChronoDuration::seconds(5))
+        );
+        timeout.extend(timestamp + ChronoDuration::seconds(12), Some(10));
+        assert_eq!(timeout.max, 10);
+        assert_eq!(
+            timeout.deadline(),
+            Some(timestamp + ChronoDuration::seconds(12))
+        );
+        assert!(!timeout.expired(timestamp + ChronoDuration::seconds(11)));
+        assert!(timeout.expired(timestamp + ChronoDuration::seconds(13)));
+    }
+
     #[test]
     fn delay_test() {
-        let time = Time::new(Offset::new(0).unwrap(), Some(2), 1);
+        let time = Time::new(Offset::new(0).unwrap(), None, Some(2), 1, None);
         assert!(!time.check_delay());
         sleep(Duration::from_secs(3));
         assert!(time.check_delay());
     }
 
+    #[test]
+    fn test_postpone() {
+        let mut time = Time::new(Offset::new(0).unwrap(), None, None, 1, None);
+        assert!(time.check_delay());
+        time.postpone(Utc::now().timestamp() + 600);
+        assert!(!time.check_delay());
+        time.postpone(Utc::now().timestamp() - 1);
+        assert!(time.check_delay());
+    }
+
+    #[test]
+    fn test_ttl() {
+        assert!(Time::new(Offset::new(0).unwrap(), None, None, 1, Some(600)).check_ttl());
+
+        let mut time = Time::new(Offset::new(0).unwrap(), None, None, 1, Some(0));
+        assert!(!time.check_ttl());
+        time.clear_ttl();
+        assert!(time.check_ttl());
+        assert!(Time::new(Offset::new(0).unwrap(), None, None, 1, None).check_ttl());
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut time = Time::new(Offset::new(3600).unwrap(), None, None, 1, None);
+        time.schedule(*time.dispatched_at() + ChronoDuration::seconds(600));
+        assert!(!time.check_delay());
+        time.schedule(*time.dispatched_at() - ChronoDuration::seconds(1));
+        assert!(time.check_delay());
+    }
+
+    #[test]
+    fn test_timezone() {
+        let timezone = "Europe/Berlin".parse().unwrap();
+        let mut time = Time::new(Offset::new(0).unwrap(), Some(timezone), None, 1, None);
+
+        let offset = time.dispatched_at().offset().local_minus_utc();
+        assert!(offset == 3600 || offset == 7200);
+        assert_eq!(time.offset().get(), offset);
+
+        // Delivery time in winter uses standard offset, and in summer - DST offset
+        let winter = DateTime::parse_from_rfc3339("2030-01-15T09:00:00+00:00").unwrap();
+        time.schedule(winter);
+        assert_eq!(time.delay().unwrap().offset().local_minus_utc(), 3600);
+        assert_eq!(time.delay().unwrap(), winter);
+
+        let summer = DateTime::parse_from_rfc3339("2030-07-15T09:00:00+00:00").unwrap();
+        time.schedule(summer);
+        assert_eq!(time.delay().unwrap().offset().local_minus_utc(), 7200);
+        assert_eq!(time.delay().unwrap(), summer);
+    }
+
     // This test covers 'fast index lookup' bug, that came in version 0.6
     #[test]
     fn test_delay_compare() {
-        let time1 = Time::new(Offset::new(0).unwrap(), Some(10), 0);
-        let time2 = Time::new(Offset::new(10).unwrap(), Some(2), 0);
+        let time1 = Time::new(Offset::new(0).unwrap(), None, Some(10), 0, None);
+        let time2 = Time::new(Offset::new(10).unwrap(), None, Some(2), 0, None);
 
         assert!(time1.get_raw_delay() > time2.get_raw_delay());
     }
@@ -38,23 +38,90 @@ pub trait Dispatchable: Identifiable {
 
     /// Check if current message is garbage
     ///
+    /// Reserved message is not garbage, even if its reservation timed out
+    ///
     /// ```
+    /// use chrono::Utc;
     /// use spartan_lib::core::message::builder::MessageBuilder;
     /// use spartan_lib::core::payload::{Dispatchable, Status};
-    /// use std::thread::sleep;
-    /// use std::time::Duration;
     ///
+    /// let now = Utc::now().timestamp();
     /// let mut message = MessageBuilder::default()
     ///     .body("Hello, world")
     ///     .timeout(0)
     ///     .compose()
     ///     .unwrap();
     ///
-    /// message.reserve();
+    /// message.reserve(now - 60);
+    ///
+    /// assert!(!message.gc());
     ///
-    /// sleep(Duration::from_secs(2));
+    /// message.time_out(now);
     ///
     /// assert!(message.gc());
     /// ```
     fn gc(&self) -> bool;
+
+    /// Check if message TTL is over
+    ///
+    /// Expired messages are not obtainable, and are collected by GC unless being processed.
+    ///
+    /// ```
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Dispatchable;
+    ///
+    /// let message = MessageBuilder::default().body("Hello, world").ttl(600).compose().unwrap();
+    ///
+    /// assert!(!message.expired());
+    ///
+    /// let message = MessageBuilder::default().body("Hello, world").ttl(0).compose().unwrap();
+    ///
+    /// assert!(message.expired());
+    /// assert!(!message.obtainable());
+    /// assert!(message.gc());
+    /// ```
+    fn expired(&self) -> bool;
+
+    /// Get UTC timestamp, since which message can be obtained
+    ///
+    /// Returns None, if message was never delayed
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Dispatchable;
+    ///
+    /// let message = MessageBuilder::default().body("Hello, world").delay(600).compose().unwrap();
+    ///
+    /// assert!(message.available_at().unwrap() >= Utc::now().timestamp() + 599);
+    /// ```
+    fn available_at(&self) -> Option<i64>;
+
+    /// Get UTC timestamp, since which message is expired
+    ///
+    /// Returns None, if message has no TTL
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Dispatchable;
+    ///
+    /// let message = MessageBuilder::default().body("Hello, world").ttl(600).compose().unwrap();
+    ///
+    /// assert!(message.expires_at().unwrap() >= Utc::now().timestamp() + 599);
+    /// ```
+    fn expires_at(&self) -> Option<i64>;
+
+    /// Get UTC timestamp of message dispatch
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Dispatchable;
+    ///
+    /// let message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    ///
+    /// assert!(message.dispatched_at() <= Utc::now().timestamp());
+    /// ```
+    fn dispatched_at(&self) -> i64;
 }
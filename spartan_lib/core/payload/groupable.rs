@@ -0,0 +1,25 @@
+use std::hash::Hash;
+
+/// Interface for working with messages, that may belong to a group
+///
+/// Messages of the same group are dispatched one at a time:
+/// while group has a reserved message, other messages of this group are skipped.
+pub trait Groupable {
+    type Group: Eq + Hash + Clone;
+
+    /// Get message group, if there is any
+    ///
+    /// ```
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Groupable;
+    ///
+    /// let message = MessageBuilder::default()
+    ///     .body("Hello, world")
+    ///     .group_id("order-1")
+    ///     .compose()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(message.group().map(|group| &**group), Some("order-1"));
+    /// ```
+    fn group(&self) -> Option<&Self::Group>;
+}
@@ -1,9 +1,11 @@
 mod dispatchable;
+mod groupable;
 mod identifiable;
 mod sortable;
 mod status;
 
 pub use dispatchable::Dispatchable;
+pub use groupable::Groupable;
 pub use identifiable::Identifiable;
 pub use sortable::Sortable;
 pub use status::Status;
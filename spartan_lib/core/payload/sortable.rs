@@ -13,4 +13,31 @@ pub trait Sortable {
     /// dbg!(message.sort());
     /// ```
     fn sort(&self) -> Self::Sort;
+
+    /// Get lowest sort key of the partition, that follows partition of provided sort key.
+    ///
+    /// [`TreeDatabase`] checks only first message of each partition when looking for available message,
+    /// so every partition must be sorted in a way, that unavailable messages come after available ones.
+    ///
+    /// Default implementation returns [`None`], so whole index is treated as a single partition.
+    ///
+    /// ```
+    /// use std::cmp::Reverse;
+    ///
+    /// use spartan_lib::core::message::{builder::MessageBuilder, Message};
+    /// use spartan_lib::core::payload::Sortable;
+    ///
+    /// let message = MessageBuilder::default()
+    ///     .body("Hello, world")
+    ///     .priority(5)
+    ///     .compose()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(Message::next_partition(&message.sort()), Some((Reverse(4), None)));
+    /// ```
+    ///
+    /// [`TreeDatabase`]: crate::core::db::TreeDatabase
+    fn next_partition(_sort: &Self::Sort) -> Option<Self::Sort> {
+        None
+    }
 }
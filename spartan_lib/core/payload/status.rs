@@ -4,29 +4,34 @@ use crate::core::payload::Dispatchable;
 pub trait Status: Dispatchable {
     /// Change message status to available
     ///
+    /// Current time is provided as UTC timestamp, so replaying the same change later gives the same result.
+    ///
     /// ```
+    /// use chrono::Utc;
     /// use spartan_lib::core::message::builder::MessageBuilder;
     /// use spartan_lib::core::payload::Status;
     ///
     /// let mut message = MessageBuilder::default().body("Hello, world").compose().unwrap();
     ///
-    /// message.requeue();
+    /// message.requeue(Utc::now().timestamp());
     /// ```
-    fn requeue(&mut self);
+    fn requeue(&mut self, now: i64);
 
     /// Change message status to "in transit"
     ///
-    /// Also, default message implementation increments counter of tries
+    /// Also, default message implementation increments counter of tries,
+    /// and records provided UTC timestamp as reservation time
     ///
     /// ```
+    /// use chrono::Utc;
     /// use spartan_lib::core::message::builder::MessageBuilder;
     /// use spartan_lib::core::payload::Status;
     ///
     /// let mut message = MessageBuilder::default().body("Hello, world").compose().unwrap();
     ///
-    /// message.reserve();
+    /// message.reserve(Utc::now().timestamp());
     /// ```
-    fn reserve(&mut self);
+    fn reserve(&mut self, now: i64);
 
     /// Check if message can be requeued
     ///
@@ -57,6 +62,7 @@ pub trait Status: Dispatchable {
     /// This method was added to help [`TreeDatabase`] correctly identify if message can be reserved later.
     ///
     /// ```
+    /// use chrono::Utc;
     /// use spartan_lib::core::message::builder::MessageBuilder;
     /// use spartan_lib::core::payload::Status;
     ///
@@ -64,11 +70,158 @@ pub trait Status: Dispatchable {
     ///
     /// assert!(message.has_tries());
     ///
-    /// message.reserve();
+    /// message.reserve(Utc::now().timestamp());
     ///
     /// assert!(!message.has_tries());
     /// ```
     ///
     /// [`TreeDatabase`]: crate::core::db::TreeDatabase
     fn has_tries(&self) -> bool;
+
+    /// Mark current reservation of message as finished by deletion
+    ///
+    /// Called with message, that was removed from database after processing.
+    /// Default message implementation records it in message reservation history.
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::message::{builder::MessageBuilder, Outcome};
+    /// use spartan_lib::core::payload::Status;
+    ///
+    /// let mut message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    /// let now = Utc::now().timestamp();
+    ///
+    /// message.reserve(now);
+    /// message.acknowledge(now);
+    ///
+    /// assert_eq!(message.history().attempts()[0].outcome(), Some(Outcome::Deleted));
+    /// ```
+    fn acknowledge(&mut self, now: i64);
+
+    /// Prepare message for moving to dead letter queue
+    ///
+    /// Default message implementation keeps try count, changes status to available,
+    /// gives message another set of tries and removes its TTL.
+    /// Reservation of message, that is still in transit, is recorded as timed out.
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Status;
+    ///
+    /// let mut message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    /// let now = Utc::now().timestamp();
+    ///
+    /// message.reserve(now);
+    /// message.requeue(now);
+    ///
+    /// assert!(!message.reservable());
+    ///
+    /// message.dead_letter(now);
+    ///
+    /// assert!(message.reservable());
+    /// ```
+    fn dead_letter(&mut self, now: i64);
+
+    /// Make message unobtainable until provided UTC timestamp
+    ///
+    /// Absolute time is used, so replaying the same change later gives the same result.
+    /// Message sort key may change, so message must not be present in database index while being postponed.
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::{Dispatchable, Status};
+    ///
+    /// let mut message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    ///
+    /// message.postpone(Utc::now().timestamp() + 600);
+    ///
+    /// assert!(!message.obtainable());
+    /// ```
+    fn postpone(&mut self, available_at: i64);
+
+    /// Record reason of message processing failure
+    ///
+    /// Default message implementation keeps only the last 10 reasons in its [`State`].
+    ///
+    /// ```
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Status;
+    ///
+    /// let mut message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    ///
+    /// message.fail("Connection refused".into());
+    ///
+    /// assert_eq!(&*message.state().failures()[0], "Connection refused");
+    /// ```
+    ///
+    /// [`State`]: crate::core::message::State
+    fn fail(&mut self, reason: Box<str>);
+
+    /// Move reservation deadline to provided UTC timestamp, optionally replacing max timeout with a new one
+    ///
+    /// Used to prevent message, that is still being processed, from being returned to queue.
+    /// Absolute deadline is used, so replaying the same change later gives the same result.
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::{Dispatchable, Status};
+    ///
+    /// let mut message = MessageBuilder::default()
+    ///     .body("Hello, world")
+    ///     .max_tries(2)
+    ///     .timeout(0)
+    ///     .compose()
+    ///     .unwrap();
+    ///
+    /// message.reserve(Utc::now().timestamp());
+    /// message.extend(Some(600), Utc::now().timestamp() + 600);
+    ///
+    /// assert!(!message.gc());
+    /// ```
+    fn extend(&mut self, timeout: Option<u32>, deadline: i64);
+
+    /// Get UTC timestamp in seconds, after which message reservation times out
+    ///
+    /// [`None`] if message is not reserved
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::message::builder::MessageBuilder;
+    /// use spartan_lib::core::payload::Status;
+    ///
+    /// let mut message = MessageBuilder::default().body("Hello, world").compose().unwrap();
+    ///
+    /// assert!(message.reservation_deadline().is_none());
+    ///
+    /// message.reserve(Utc::now().timestamp());
+    ///
+    /// assert!(message.reservation_deadline().is_some());
+    /// ```
+    fn reservation_deadline(&self) -> Option<i64>;
+
+    /// Return message, whose reservation timed out, back to available status
+    ///
+    /// Default message implementation records timeout in message reservation history.
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use spartan_lib::core::message::{builder::MessageBuilder, Outcome};
+    /// use spartan_lib::core::payload::Status;
+    ///
+    /// let mut message = MessageBuilder::default()
+    ///     .body("Hello, world")
+    ///     .max_tries(2)
+    ///     .compose()
+    ///     .unwrap();
+    ///
+    /// message.reserve(Utc::now().timestamp());
+    /// message.time_out(Utc::now().timestamp());
+    ///
+    /// assert!(message.reservable());
+    /// assert_eq!(message.history().attempts()[0].outcome(), Some(Outcome::TimedOut));
+    /// ```
+    fn time_out(&mut self, now: i64);
 }
@@ -6,4 +6,5 @@ extern crate thiserror;
 pub mod core;
 
 pub use chrono;
+pub use chrono_tz;
 pub use uuid;